@@ -0,0 +1,277 @@
+//! Sample chess agent for nets (guest ABI 2).
+//!
+//! Before each `decide(len)` the host writes the position as FEN into the
+//! exported `NETS_OBS` buffer, whose size `NETS_OBS_LEN` tells the host;
+//! `len` is the number of bytes written. The
+//! answer is `from | to << 6 | promotion << 12`, with squares numbered
+//! a1 = 0 to h8 = 63 and promotion 0 (none), 1 (knight), 2 (bishop),
+//! 3 (rook) or 4 (queen).
+//!
+//! It plays the legal move that captures the most valuable piece, which
+//! is enough to show the ABI end to end.
+
+/// Observation buffer the host writes into; must hold 256 bytes.
+#[no_mangle]
+pub static mut NETS_OBS: [u8; 256] = [0; 256];
+
+/// Size of `NETS_OBS`, checked by the host when the agent is loaded.
+#[no_mangle]
+pub static NETS_OBS_LEN: u32 = 256;
+
+#[no_mangle]
+pub extern "C" fn decide(len: u64) -> u64 {
+    // SAFETY: the host only writes the buffer between calls.
+    let obs = unsafe { &*std::ptr::addr_of!(NETS_OBS) };
+    let fen = &obs[..(len as usize).min(obs.len())];
+
+    match Position::from_fen(fen) {
+        Some(pos) => pos.choose().map(|m| m.encode()).unwrap_or(0),
+        None => 0,
+    }
+}
+
+const PAWN: i8 = 1;
+const KNIGHT: i8 = 2;
+const BISHOP: i8 = 3;
+const ROOK: i8 = 4;
+const QUEEN: i8 = 5;
+const KING: i8 = 6;
+
+const KNIGHT_STEPS: [(i8, i8); 8] =
+    [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+const KING_STEPS: [(i8, i8); 8] =
+    [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+const ROOK_DIRS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const BISHOP_DIRS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+#[derive(Clone, Copy)]
+struct Move {
+    from: usize,
+    to: usize,
+    /// Piece promoted to, `KNIGHT` to `QUEEN`, or 0.
+    promotion: i8,
+}
+
+impl Move {
+    fn encode(self) -> u64 {
+        let promotion = if self.promotion == 0 { 0 } else { (self.promotion - 1) as u64 };
+        self.from as u64 | (self.to as u64) << 6 | promotion << 12
+    }
+}
+
+/// The square `(df, dr)` away from `sq`, if it is on the board.
+fn offset(sq: usize, (df, dr): (i8, i8)) -> Option<usize> {
+    let f = (sq % 8) as i8 + df;
+    let r = (sq / 8) as i8 + dr;
+    ((0..8).contains(&f) && (0..8).contains(&r)).then(|| (r * 8 + f) as usize)
+}
+
+#[derive(Clone)]
+struct Position {
+    /// Pieces by square; positive for White, negative for Black.
+    board: [i8; 64],
+    /// 1 for White to move, -1 for Black.
+    side: i8,
+    /// White king side, White queen side, Black king side, Black queen side.
+    castling: [bool; 4],
+    en_passant: Option<usize>,
+}
+
+impl Position {
+    fn from_fen(fen: &[u8]) -> Option<Position> {
+        let fen = std::str::from_utf8(fen).ok()?;
+        let mut fields = fen.split_whitespace();
+
+        let mut board = [0; 64];
+        for (i, rank) in fields.next()?.split('/').enumerate() {
+            let mut file = 0;
+            for c in rank.chars() {
+                if let Some(n) = c.to_digit(10) {
+                    file += n as usize;
+                    continue;
+                }
+                let piece = match c.to_ascii_lowercase() {
+                    'p' => PAWN,
+                    'n' => KNIGHT,
+                    'b' => BISHOP,
+                    'r' => ROOK,
+                    'q' => QUEEN,
+                    'k' => KING,
+                    _ => return None,
+                };
+                let sq = (7usize.checked_sub(i)?) * 8 + file;
+                *board.get_mut(sq)? = if c.is_ascii_uppercase() { piece } else { -piece };
+                file += 1;
+            }
+        }
+
+        let side = if fields.next()? == "b" { -1 } else { 1 };
+        let rights = fields.next().unwrap_or("-");
+        let castling = ['K', 'Q', 'k', 'q'].map(|c| rights.contains(c));
+        let en_passant = fields.next().and_then(|s| match s.as_bytes() {
+            [f @ b'a'..=b'h', r @ b'1'..=b'8'] => Some(((r - b'1') * 8 + (f - b'a')) as usize),
+            _ => None,
+        });
+        Some(Position { board, side, castling, en_passant })
+    }
+
+    /// Whether a piece of `by` attacks `sq`.
+    fn attacked(&self, sq: usize, by: i8) -> bool {
+        let hits = |steps: &[(i8, i8)], piece: i8| {
+            steps.iter().any(|&d| offset(sq, d).is_some_and(|s| self.board[s] == piece * by))
+        };
+        let slides = |dirs: &[(i8, i8)], pieces: [i8; 2]| {
+            dirs.iter().any(|&d| {
+                let mut at = sq;
+                while let Some(next) = offset(at, d) {
+                    match self.board[next] {
+                        0 => at = next,
+                        p => return pieces.iter().any(|&q| p == q * by),
+                    }
+                }
+                false
+            })
+        };
+
+        // A pawn of `by` attacks from one rank behind, seen from `by`'s side.
+        hits(&[(-1, -by), (1, -by)], PAWN)
+            || hits(&KNIGHT_STEPS, KNIGHT)
+            || hits(&KING_STEPS, KING)
+            || slides(&ROOK_DIRS, [ROOK, QUEEN])
+            || slides(&BISHOP_DIRS, [BISHOP, QUEEN])
+    }
+
+    fn in_check(&self, side: i8) -> bool {
+        match self.board.iter().position(|&p| p == KING * side) {
+            Some(king) => self.attacked(king, -side),
+            None => false,
+        }
+    }
+
+    /// Moves that follow the pieces' movement rules, ignoring checks.
+    fn pseudo_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        let side = self.side;
+        let mine = |p: i8| p * side > 0;
+        let theirs = |p: i8| p * side < 0;
+
+        for from in 0..64 {
+            let piece = self.board[from] * side;
+            if piece <= 0 {
+                continue;
+            }
+
+            match piece {
+                PAWN => {
+                    let mut targets = Vec::new();
+                    if let Some(one) = offset(from, (0, side)).filter(|&s| self.board[s] == 0) {
+                        targets.push(one);
+                        let home = if side == 1 { 1 } else { 6 };
+                        if from / 8 == home {
+                            if let Some(two) = offset(one, (0, side)) {
+                                if self.board[two] == 0 {
+                                    targets.push(two);
+                                }
+                            }
+                        }
+                    }
+                    for df in [-1, 1] {
+                        if let Some(s) = offset(from, (df, side)) {
+                            if theirs(self.board[s]) || self.en_passant == Some(s) {
+                                targets.push(s);
+                            }
+                        }
+                    }
+                    for to in targets {
+                        if to / 8 == 0 || to / 8 == 7 {
+                            for promotion in [QUEEN, ROOK, BISHOP, KNIGHT] {
+                                moves.push(Move { from, to, promotion });
+                            }
+                        } else {
+                            moves.push(Move { from, to, promotion: 0 });
+                        }
+                    }
+                }
+                KNIGHT | KING => {
+                    let steps = if piece == KNIGHT { &KNIGHT_STEPS } else { &KING_STEPS };
+                    for &d in steps {
+                        if let Some(to) = offset(from, d).filter(|&s| !mine(self.board[s])) {
+                            moves.push(Move { from, to, promotion: 0 });
+                        }
+                    }
+                }
+                _ => {
+                    let dirs: &[(i8, i8)] = match piece {
+                        BISHOP => &BISHOP_DIRS,
+                        ROOK => &ROOK_DIRS,
+                        _ => &KING_STEPS,
+                    };
+                    for &d in dirs {
+                        let mut at = from;
+                        while let Some(to) = offset(at, d) {
+                            if mine(self.board[to]) {
+                                break;
+                            }
+                            moves.push(Move { from, to, promotion: 0 });
+                            if theirs(self.board[to]) {
+                                break;
+                            }
+                            at = to;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Castling: the king may not start in, pass through or land in check.
+        let (rank, rights) = if side == 1 { (0, [0, 1]) } else { (56, [2, 3]) };
+        let king = rank + 4;
+        if self.board[king] == KING * side && !self.in_check(side) {
+            for (right, empty, path, to) in [
+                (rights[0], &[5, 6][..], [5, 6], 6),
+                (rights[1], &[1, 2, 3][..], [3, 2], 2),
+            ] {
+                let clear = empty.iter().all(|&f| self.board[rank + f] == 0);
+                let safe = path.iter().all(|&f| !self.attacked(rank + f, -side));
+                if self.castling[right] && clear && safe {
+                    moves.push(Move { from: king, to: rank + to, promotion: 0 });
+                }
+            }
+        }
+        moves
+    }
+
+    fn apply(&self, m: Move) -> Position {
+        let mut next = self.clone();
+        let piece = next.board[m.from];
+        next.board[m.from] = 0;
+        next.board[m.to] = if m.promotion != 0 { m.promotion * self.side } else { piece };
+
+        if piece.abs() == PAWN && self.en_passant == Some(m.to) {
+            if let Some(taken) = offset(m.to, (0, -self.side)) {
+                next.board[taken] = 0;
+            }
+        }
+        if piece.abs() == KING && m.from.abs_diff(m.to) == 2 {
+            let (rook_from, rook_to) =
+                if m.to > m.from { (m.from + 3, m.from + 1) } else { (m.from - 4, m.from - 1) };
+            next.board[rook_to] = next.board[rook_from];
+            next.board[rook_from] = 0;
+        }
+        next.side = -self.side;
+        next
+    }
+
+    fn legal_moves(&self) -> Vec<Move> {
+        self.pseudo_moves()
+            .into_iter()
+            .filter(|&m| !self.apply(m).in_check(self.side))
+            .collect()
+    }
+
+    /// The legal move taking the most valuable piece, promoting if it can.
+    fn choose(&self) -> Option<Move> {
+        let gain = |m: &Move| self.board[m.to].abs() * 8 + m.promotion;
+        self.legal_moves().into_iter().max_by_key(gain)
+    }
+}
@@ -0,0 +1,24 @@
+use std::fs;
+
+/// Expose the nets-core version so run records can say which rules
+/// produced them.
+fn main() {
+    let manifest = "../nets/Cargo.toml";
+    println!("cargo:rerun-if-changed={}", manifest);
+
+    let version = fs::read_to_string(manifest)
+        .ok()
+        .and_then(|s| {
+            s.lines()
+                .skip_while(|l| l.trim() != "[package]")
+                .skip(1)
+                .take_while(|l| !l.trim_start().starts_with('['))
+                .find_map(|l| {
+                    let (key, value) = l.split_once('=')?;
+                    (key.trim() == "version").then(|| value.trim().trim_matches('"').to_string())
+                })
+        })
+        .unwrap_or_else(|| "unknown".into());
+
+    println!("cargo:rustc-env=NETS_CORE_VERSION={}", version);
+}
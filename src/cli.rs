@@ -1,35 +1,57 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+
+use crate::commands::league::Tier;
+use crate::commands::leaderboard::SortKey;
+use crate::commands::output::OutputFormat;
+use crate::commands::payout::PayoutPolicy;
+use crate::commands::tournament::Format;
+use crate::commands::trace::TraceFormat;
+use crate::systems::guest::Limits;
 
 #[derive(Parser)]
 #[command(name = "nets")]
 #[command(about = "CLI for nets-core competitive agent markets")]
 pub struct Cli {
+    /// Workspace directory holding nets.toml, state and agents
+    #[arg(long, global = true)]
+    pub workspace: Option<String>,
+    /// Config file (defaults to <workspace>/nets.toml)
+    #[arg(long, global = true)]
+    pub config: Option<String>,
+    /// Output format: text, json or ndjson
+    #[arg(long, global = true, default_value = "text")]
+    pub format: OutputFormat,
+
     #[command(subcommand)]
     pub command: Command,
 }
 
 #[derive(Subcommand)]
 pub enum Command {
-    Run {
-        #[arg(long, default_value = "snake")]
-        system: String,
-        #[arg(long, default_value_t = 1)]
-        matches: usize,
-        #[arg(long)]
-        commit: bool,
-        #[arg(long)]
-        wallet: Option<String>,
-    },
+    Run(RunArgs),
+
+    /// Play two agents head to head without touching the ledger
+    Match(MatchArgs),
+
+    /// Play a system yourself against an agent, or watch the agent play
+    Play(PlayArgs),
+
+    /// Step through a match frame by frame in the terminal
+    Replay(ReplayArgs),
 
     Verify {
         #[arg(long)]
         agent: String,
+        #[arg(long)]
+        system: Option<String>,
     },
 
     Export {
         #[arg(long)]
         agent: String,
         #[arg(long)]
+        system: Option<String>,
+        #[arg(long)]
         out: String,
     },
 
@@ -40,6 +62,12 @@ pub enum Command {
         agent_wasm: String,
     },
 
+    /// Commit a plan saved by `nets run --plan-out`
+    Apply {
+        #[arg(long)]
+        plan: String,
+    },
+
     /// Generate a step-level fraud proof
     ProveFraud {
         #[arg(long)]
@@ -64,8 +92,9 @@ pub enum Command {
     Slash {
         #[arg(long)]
         agent: String,
+        /// Defaults to `slash_amount` from the workspace config
         #[arg(long)]
-        amount: u64,
+        amount: Option<u64>,
     },
 
     Agent {
@@ -73,6 +102,40 @@ pub enum Command {
         action: AgentCommand,
     },
 
+    League {
+        #[command(subcommand)]
+        action: LeagueCommand,
+    },
+
+    /// Show per-system Elo and Glicko-2 ratings, or one agent's history
+    Ratings {
+        #[arg(long)]
+        system: Option<String>,
+        /// Show this agent's rating history
+        #[arg(long)]
+        agent: Option<String>,
+    },
+
+    /// Rank agents by balance, rating or wins, with changes since the last run
+    Leaderboard {
+        #[arg(long)]
+        system: Option<String>,
+        /// balance, rating or wins
+        #[arg(long, default_value = "balance")]
+        sort: SortKey,
+    },
+
+    /// Inspect recorded runs
+    History {
+        #[command(subcommand)]
+        action: HistoryCommand,
+    },
+
+    Trace {
+        #[command(subcommand)]
+        action: TraceCommand,
+    },
+
     Balance {
         #[arg(long)]
         agent: Option<String>,
@@ -87,4 +150,238 @@ pub enum AgentCommand {
         #[arg(long)]
         path: String,
     },
+    /// List discovered agents with their manifests
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum LeagueCommand {
+    /// Show which agents sit in which tier
+    Status,
+}
+
+#[derive(Subcommand)]
+pub enum HistoryCommand {
+    /// List recorded runs, oldest first
+    List {
+        #[arg(long)]
+        system: Option<String>,
+        /// Only runs this agent took part in, with its result
+        #[arg(long)]
+        agent: Option<String>,
+        /// Only the most recent N runs
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Show one run in full
+    Show {
+        run_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TraceCommand {
+    /// Convert a trace file or committed match for other tools, e.g.
+    /// chess games to PGN
+    Export {
+        /// Trace file written by `nets match --out-dir`
+        #[arg(long, conflicts_with = "agent", required_unless_present = "agent")]
+        trace: Option<String>,
+        /// Export a match of this agent's last committed run instead
+        #[arg(long, requires = "match_index")]
+        agent: Option<String>,
+        /// The committed match to export, as listed by `nets replay --agent`
+        #[arg(long = "match", value_name = "INDEX", requires = "agent")]
+        match_index: Option<usize>,
+        /// Target notation (`--format` is the global output format)
+        #[arg(long, default_value = "pgn")]
+        to: TraceFormat,
+        /// The agent's .wasm; defaults to <agents_dir>/<agent>.wasm
+        #[arg(long)]
+        agent_wasm: Option<String>,
+        /// Write here instead of stdout
+        #[arg(long)]
+        out: Option<String>,
+    },
+}
+
+#[derive(Args)]
+pub struct RunArgs {
+    /// Defaults to `default_system` from the workspace config
+    #[arg(long)]
+    pub system: Option<String>,
+    #[arg(long, default_value_t = 1)]
+    pub matches: usize,
+    #[arg(long)]
+    pub commit: bool,
+    /// Keep a history record of a dry run (committed runs always get one)
+    #[arg(long)]
+    pub record: bool,
+    /// Save the dry run as a plan that `nets apply` can commit later
+    #[arg(long, conflicts_with = "commit")]
+    pub plan_out: Option<String>,
+    /// Wallet for every agent, overriding manifest owners
+    #[arg(long)]
+    pub wallet: Option<String>,
+    /// Only these agents; ids or glob patterns, comma-separated
+    #[arg(long, value_delimiter = ',')]
+    pub agents: Vec<String>,
+    /// Skip these agents; ids or glob patterns, comma-separated
+    #[arg(long, value_delimiter = ',')]
+    pub exclude: Vec<String>,
+    /// Only agents whose manifest carries this tag (repeatable; all must match)
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+    /// Override a system parameter (repeatable), e.g. `--param width=20`
+    #[arg(long = "param", value_name = "KEY=VALUE")]
+    pub params: Vec<String>,
+    /// JSON file with system parameters, applied before `--param`
+    #[arg(long)]
+    pub params_file: Option<String>,
+    /// Payout policy, e.g. `top-n:100,50` or `proportional:1000`
+    #[arg(long)]
+    pub payout: Option<PayoutPolicy>,
+    /// League tier to run (bronze, silver, gold); only its members play
+    #[arg(long)]
+    pub league: Option<Tier>,
+    /// Run seed; every match seed is derived from it
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+    /// league, round-robin, swiss:<rounds>, single-elim or double-elim
+    #[arg(long, default_value = "league")]
+    pub tournament: Format,
+    /// Worker threads for match execution; output is identical for any value
+    #[arg(long, default_value_t = 1)]
+    pub jobs: usize,
+    #[command(flatten)]
+    pub limits: LimitArgs,
+}
+
+#[derive(Args)]
+pub struct MatchArgs {
+    /// Defaults to `default_system` from the workspace config
+    #[arg(long)]
+    pub system: Option<String>,
+    /// First agent's .wasm
+    #[arg(long)]
+    pub a: String,
+    /// Second agent's .wasm
+    #[arg(long)]
+    pub b: String,
+    #[arg(long, default_value_t = 1)]
+    pub games: usize,
+    /// Game `k` is played with a seed derived from this one
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+    #[arg(long)]
+    pub league: Option<Tier>,
+    /// Override a system parameter (repeatable), e.g. `--param width=20`
+    #[arg(long = "param", value_name = "KEY=VALUE")]
+    pub params: Vec<String>,
+    /// JSON file with system parameters, applied before `--param`
+    #[arg(long)]
+    pub params_file: Option<String>,
+    /// Write each game's traces and commitments to this directory
+    #[arg(long)]
+    pub out_dir: Option<String>,
+    #[command(flatten)]
+    pub limits: LimitArgs,
+}
+
+#[derive(Args)]
+pub struct PlayArgs {
+    /// Defaults to `default_system` from the workspace config
+    #[arg(long)]
+    pub system: Option<String>,
+    /// The agent's .wasm
+    #[arg(long)]
+    pub agent: String,
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+    /// Only watch the agent play
+    #[arg(long)]
+    pub watch: bool,
+    /// Show the agent's steps without waiting for Enter
+    #[arg(long)]
+    pub auto: bool,
+    #[arg(long)]
+    pub league: Option<Tier>,
+    /// Override a system parameter (repeatable), e.g. `--param width=20`
+    #[arg(long = "param", value_name = "KEY=VALUE")]
+    pub params: Vec<String>,
+    /// JSON file with system parameters, applied before `--param`
+    #[arg(long)]
+    pub params_file: Option<String>,
+    #[command(flatten)]
+    pub limits: LimitArgs,
+}
+
+#[derive(Args)]
+pub struct ReplayArgs {
+    /// Trace file written by `nets match --out-dir`; replays its system,
+    /// params, seed and limits
+    #[arg(long, conflicts_with = "agent", required_unless_present = "agent")]
+    pub trace: Option<String>,
+    /// Agent id to replay instead of a trace file. Without `--match` or
+    /// `--seed`, lists the matches of its last committed run
+    #[arg(long)]
+    pub agent: Option<String>,
+    /// The agent's .wasm; defaults to <agents_dir>/<agent>.wasm
+    #[arg(long)]
+    pub agent_wasm: Option<String>,
+    /// Replay this match of the agent's last committed run, with the
+    /// system, params, seed, limits and table it was played with
+    #[arg(
+        long = "match",
+        value_name = "INDEX",
+        requires = "agent",
+        conflicts_with_all = ["trace", "seed", "system", "params", "params_file"]
+    )]
+    pub match_index: Option<usize>,
+    /// Defaults to `default_system` from the workspace config
+    #[arg(long, conflicts_with = "trace")]
+    pub system: Option<String>,
+    /// Play a new match on this match seed instead of a committed one
+    #[arg(long, conflicts_with = "trace")]
+    pub seed: Option<u64>,
+    /// Override a system parameter (repeatable), e.g. `--param width=20`
+    #[arg(long = "param", value_name = "KEY=VALUE", conflicts_with = "trace")]
+    pub params: Vec<String>,
+    /// JSON file with system parameters, applied before `--param`
+    #[arg(long, conflicts_with = "trace")]
+    pub params_file: Option<String>,
+    /// Milliseconds between frames while playing
+    #[arg(long, default_value_t = 150)]
+    pub delay: u64,
+    #[command(flatten)]
+    pub limits: LimitArgs,
+}
+
+/// Overrides for the workspace `[limits]`.
+#[derive(Args)]
+pub struct LimitArgs {
+    /// Fuel one agent decision may burn
+    #[arg(long)]
+    pub fuel_per_decision: Option<u64>,
+    /// Fuel all of an agent's decisions in one match may burn
+    #[arg(long)]
+    pub fuel_per_match: Option<u64>,
+    /// Agent linear memory cap, in 64 KiB pages
+    #[arg(long)]
+    pub memory_pages: Option<u32>,
+}
+
+impl LimitArgs {
+    pub fn apply(&self, mut limits: Limits) -> Limits {
+        if let Some(v) = self.fuel_per_decision {
+            limits.fuel_per_decision = v;
+        }
+        if let Some(v) = self.fuel_per_match {
+            limits.fuel_per_match = v;
+        }
+        if let Some(v) = self.memory_pages {
+            limits.memory_pages = v;
+        }
+        limits
+    }
 }
@@ -1,6 +1,11 @@
+use serde::Serialize;
+
 use crate::cli::AgentCommand;
+use crate::commands::discovery::{self, AgentManifest};
+use crate::commands::output::OutputFormat;
+use crate::config::Config;
 
-pub fn handle(cmd: AgentCommand) {
+pub fn handle(cfg: &Config, cmd: AgentCommand, format: OutputFormat) {
     match cmd {
         AgentCommand::Build { path } => {
             println!("building guest agent at path: {}", path);
@@ -10,5 +15,77 @@ pub fn handle(cmd: AgentCommand) {
             // - enforce wasm32-unknown-unknown
             // - run cargo build safely
         }
+        AgentCommand::List => list(cfg, format),
+    }
+}
+
+/// `nets agent list` JSON output.
+#[derive(Serialize)]
+pub struct AgentList {
+    pub agents: Vec<ListedAgent>,
+}
+
+#[derive(Serialize)]
+pub struct ListedAgent {
+    pub id: String,
+    pub name: String,
+    pub wasm: String,
+    /// Absent when the manifest could not be read; see `manifest_error`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<AgentManifest>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest_error: Option<String>,
+    pub abi_supported: bool,
+}
+
+fn list(cfg: &Config, format: OutputFormat) {
+    let agents = discovery::discover(&cfg.agents_dir);
+    if agents.is_empty() && format.is_text() {
+        println!("no agents in {}", cfg.agents_dir);
+        return;
+    }
+
+    let mut report = AgentList { agents: Vec::new() };
+    for agent in agents {
+        if format.is_text() {
+            print_agent(&agent);
+        }
+        let readable = agent.manifest_error.is_none();
+        report.agents.push(ListedAgent {
+            id: agent.id.clone(),
+            name: agent.name().to_string(),
+            wasm: agent.wasm_path.display().to_string(),
+            abi_supported: readable && agent.manifest.abi_supported(),
+            manifest: readable.then_some(agent.manifest),
+            manifest_error: agent.manifest_error,
+        });
+    }
+
+    format.emit("agents", &report);
+}
+
+fn print_agent(agent: &discovery::DiscoveredAgent) {
+    let m = &agent.manifest;
+    println!("{} ({})", agent.id, agent.name());
+    println!("  wasm: {}", agent.wasm_path.display());
+    if let Some(e) = &agent.manifest_error {
+        println!("  skipped: {}", e);
+        return;
+    }
+    println!("  owner_wallet: {}", m.owner_wallet.as_deref().unwrap_or("-"));
+    println!(
+        "  systems: {}",
+        if m.systems.is_empty() { "any".to_string() } else { m.systems.join(", ") }
+    );
+    println!(
+        "  abi_version: {}{}",
+        m.abi_version.unwrap_or(discovery::AGENT_ABI_VERSION),
+        if m.abi_supported() { "" } else { " (unsupported)" }
+    );
+    if !m.tags.is_empty() {
+        println!("  tags: {}", m.tags.join(", "));
+    }
+    if let Some(d) = &m.description {
+        println!("  description: {}", d);
     }
 }
@@ -1,32 +1,91 @@
+use serde::Serialize;
+
 use nets::{ledger::Ledger, league_state::LeagueState, persist};
 
+use crate::commands::output::OutputFormat;
+use crate::config::Config;
 use crate::wallet::mock::MockWalletAdapter;
 use crate::wallet::adapter::WalletAdapter;
 
-pub fn show(agent: Option<String>, wallet: Option<String>) {
+/// `nets balance` JSON output. Only the requested sections are present.
+#[derive(Serialize)]
+pub struct BalanceReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agents: Option<Vec<AgentBalance>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wallets: Option<Vec<WalletBalance>>,
+}
+
+#[derive(Serialize)]
+pub struct AgentBalance {
+    pub agent: String,
+    pub balance: u64,
+}
+
+#[derive(Serialize)]
+pub struct WalletBalance {
+    pub wallet: String,
+    pub balance: u64,
+}
+
+pub fn show(cfg: &Config, agent: Option<String>, wallet: Option<String>, format: OutputFormat) {
     let mut ledger = Ledger::new();
     let mut league_state = LeagueState::default();
-    let _ = persist::load("state.json", &mut ledger, &mut league_state);
+    let _ = persist::load(&cfg.state_path, &mut ledger, &mut league_state);
+
+    let wallet_adapter = MockWalletAdapter::load(&cfg.state_path);
 
-    let wallet_adapter = MockWalletAdapter::load("state.json");
+    let report = if let Some(a) = agent {
+        let balance = ledger.balance(&a);
+        if format.is_text() {
+            println!("{} balance={}", a, balance);
+        }
+        BalanceReport {
+            agents: Some(vec![AgentBalance { agent: a, balance }]),
+            wallets: None,
+        }
+    } else if let Some(w) = wallet {
+        let balance = wallet_adapter.balance(&w);
+        if format.is_text() {
+            println!("wallet {} balance={}", w, balance);
+        }
+        BalanceReport {
+            agents: None,
+            wallets: Some(vec![WalletBalance { wallet: w, balance }]),
+        }
+    } else {
+        let mut agents: Vec<AgentBalance> = ledger
+            .snapshot()
+            .into_iter()
+            .map(|(id, _)| {
+                let agent = id.to_string();
+                AgentBalance { balance: ledger.balance(&agent), agent }
+            })
+            .collect();
+        let mut wallets: Vec<WalletBalance> = wallet_adapter
+            .all_wallets()
+            .iter()
+            .map(|(w, b)| WalletBalance { wallet: w.clone(), balance: *b })
+            .collect();
+        // Neither the ledger nor the wallets promise an order; sort so
+        // output is stable.
+        agents.sort_by(|a, b| a.agent.cmp(&b.agent));
+        wallets.sort_by(|a, b| a.wallet.cmp(&b.wallet));
 
-    if let Some(a) = agent {
-        println!("{} balance={}", a, ledger.balance(&a));
-        return;
-    }
+        if format.is_text() {
+            println!("agent balances:");
+            for a in &agents {
+                println!("{} balance={}", a.agent, a.balance);
+            }
 
-    if let Some(w) = wallet {
-        println!("wallet {} balance={}", w, wallet_adapter.balance(&w));
-        return;
-    }
+            println!("\nwallet balances:");
+            for w in &wallets {
+                println!("{} balance={}", w.wallet, w.balance);
+            }
+        }
 
-    println!("agent balances:");
-    for (id, bal) in ledger.snapshot() {
-        println!("{} balance={}", id, bal);
-    }
+        BalanceReport { agents: Some(agents), wallets: Some(wallets) }
+    };
 
-    println!("\nwallet balances:");
-    for (w, b) in wallet_adapter.all_wallets() {
-        println!("{} balance={}", w, b);
-    }
+    format.emit("balance", &report);
 }
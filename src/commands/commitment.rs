@@ -1,18 +1,187 @@
+use std::collections::BTreeMap;
+use std::fs;
+
 use serde::{Serialize, Deserialize};
 
-#[derive(Serialize, Deserialize)]
+use nets::match_trace::MatchTrace;
+
+use crate::commands::{state_file, trace_file::{self, TraceFile, TraceFileStep}};
+use crate::config::Config;
+use crate::systems::{self, aggregate_root, guest::Limits};
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Commitment {
     pub system: String,
     pub agent: String,
+    /// Merkle root over the roots in `matches`, or the single match root
+    /// when `matches` is empty.
     pub merkle_root: String,
-    pub system_params: SystemParams,
+    /// Parameters of the system named in `system`; decoded by its plugin.
+    pub system_params: serde_json::Value,
+    /// Run seed, or the seed of the single committed match. Commitments
+    /// made before seeds were recorded replay with the default seed 0.
+    #[serde(default)]
+    pub seed: u64,
+    /// Agent resource limits the matches were played under; commitments
+    /// made before limits existed replay unmetered, as they were played.
+    #[serde(default = "Limits::unmetered")]
+    pub limits: Limits,
+    /// Observation encoding guests were played with (`OBS_ENCODING`);
+    /// 0 for commitments made before it was recorded.
+    #[serde(default)]
+    pub encoding: u32,
+    /// Every agent at the table of a single shared-board match, in seat
+    /// order; empty when the agent played alone.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lineup: Vec<String>,
+    /// Step hashes of a single-match commitment written next to its
+    /// trace file; run commitments reference trace files instead.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<TraceFileStep>,
+    /// The matches that scored the agent, in the order played. Empty for
+    /// single-match commitments and those made before runs recorded them.
+    #[serde(default)]
+    pub matches: Vec<CommittedMatch>,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct SystemParams {
-    pub width: i32,
-    pub height: i32,
-    pub steps: u64,
+/// One match behind a commitment.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CommittedMatch {
+    pub seed: u64,
+    pub merkle_root: String,
+    /// Every agent at the table in seat order, for shared boards.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lineup: Vec<String>,
+    /// Trace file in the workspace's traces directory holding the agent's
+    /// steps, whose Merkle root is `merkle_root`. They let `prove-fraud`
+    /// point at the committed step a replay disagrees with; absent for
+    /// commitments made before trace files were written.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<String>,
+}
+
+impl Commitment {
+    /// Every committed match; a single-match commitment is its own match.
+    pub fn committed_matches(&self) -> Vec<CommittedMatch> {
+        if self.matches.is_empty() {
+            vec![CommittedMatch {
+                seed: self.seed,
+                merkle_root: self.merkle_root.clone(),
+                lineup: self.lineup.clone(),
+                trace: None,
+            }]
+        } else {
+            self.matches.clone()
+        }
+    }
+
+    /// Committed match `index` as a trace file, so it can be replayed or
+    /// exported like one. Its steps are read from the match's trace file
+    /// in `traces_dir`; `None` if there is no match `index`.
+    pub fn match_file(
+        &self,
+        traces_dir: &str,
+        index: usize,
+    ) -> Option<Result<TraceFile, String>> {
+        let m = self.committed_matches().into_iter().nth(index)?;
+        let steps = match &m.trace {
+            Some(name) => trace_file::committed_steps(traces_dir, name, &m.merkle_root),
+            None if self.matches.is_empty() => Ok(self.steps.clone()),
+            None => Ok(Vec::new()),
+        };
+        Some(steps.map(|steps| TraceFile {
+            system: self.system.clone(),
+            agent: self.agent.clone(),
+            system_params: self.system_params.clone(),
+            seed: m.seed,
+            limits: self.limits,
+            encoding: self.encoding,
+            merkle_root: m.merkle_root,
+            lineup: m.lineup,
+            steps,
+        }))
+    }
+
+    /// Root of the committed matches: their aggregate, or the one root.
+    pub fn root_of(&self, roots: &[[u8; 32]]) -> [u8; 32] {
+        if self.matches.is_empty() {
+            roots[0]
+        } else {
+            aggregate_root(roots)
+        }
+    }
+}
+
+/// Every committed match replayed from the agent's wasm.
+pub struct Replay {
+    pub traces: Vec<MatchTrace>,
+    /// Root recomputed the way `merkle_root` was committed.
+    pub root: [u8; 32],
+    /// First match whose recomputed root differs from the committed one.
+    pub mismatch: Option<usize>,
+}
+
+/// Replay `commitment` with the agent's `wasm`. Shared-board matches also
+/// need every other agent at the table, read with `opponent`.
+pub fn replay(
+    commitment: &Commitment,
+    wasm: &[u8],
+    opponent: impl Fn(&str) -> Result<Vec<u8>, String>,
+) -> Result<Replay, String> {
+    let plugin = systems::resolve(&commitment.system);
+    let matches = commitment.committed_matches();
+
+    let mut traces = Vec::new();
+    for m in &matches {
+        let (seats, seat) = seat_table(&commitment.agent, &m.lineup, wasm, &opponent)?;
+        let table: Vec<(&str, &[u8])> =
+            seats.iter().map(|(id, w)| (id.as_str(), w.as_slice())).collect();
+
+        traces.push(plugin.replay(
+            &commitment.system_params,
+            m.seed,
+            &table,
+            seat,
+            &commitment.limits,
+            commitment.encoding,
+        )?);
+    }
+
+    let roots: Vec<[u8; 32]> = traces.iter().map(|t| t.merkle.root()).collect();
+    let mismatch = matches
+        .iter()
+        .zip(&roots)
+        .position(|(m, root)| m.merkle_root != hex::encode(root));
+
+    Ok(Replay { root: commitment.root_of(&roots), traces, mismatch })
+}
+
+/// Agent ids with their wasm, in seat order.
+pub type Table = Vec<(String, Vec<u8>)>;
+
+/// Every seat of a match with `lineup` (just `agent` when empty) with its
+/// wasm, and `agent`'s seat. `wasm` is the agent's own; the others are
+/// read with `opponent`.
+pub fn seat_table(
+    agent: &str,
+    lineup: &[String],
+    wasm: &[u8],
+    opponent: impl Fn(&str) -> Result<Vec<u8>, String>,
+) -> Result<(Table, usize), String> {
+    let lineup = if lineup.is_empty() { vec![agent.to_string()] } else { lineup.to_vec() };
+    let seat = lineup
+        .iter()
+        .position(|id| id == agent)
+        .ok_or_else(|| format!("{} is not in the lineup of its match", agent))?;
+
+    let seats = lineup
+        .into_iter()
+        .map(|id| {
+            let w = if id == agent { wasm.to_vec() } else { opponent(&id)? };
+            Ok((id, w))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok((seats, seat))
 }
 
 #[derive(Serialize, Deserialize)]
@@ -20,5 +189,74 @@ pub struct FraudProofEnvelope {
     pub agent: String,
     pub system: String,
     pub committed_root: String,
+    /// Index into the commitment's `matches` and that match's root, which
+    /// the step proof is checked against. Absent for single-match
+    /// commitments, where the committed root is the match root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_index: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_root: Option<String>,
     pub proof: nets::fraud::TraceStepProof,
 }
+
+/// The commitment behind `root`, the root nets-core persisted for `agent`.
+/// Roots committed before full commitments were recorded get `legacy`.
+pub fn for_root(
+    cfg: &Config,
+    agent: &str,
+    root: [u8; 32],
+    system: Option<String>,
+) -> Commitment {
+    match load_records(&cfg.state_path).remove(agent) {
+        Some(record) if record.merkle_root == hex::encode(root) => record,
+        _ => legacy(cfg, agent, root, system),
+    }
+}
+
+/// A root committed before seeds, parameters, limits and encodings were
+/// recorded: one match of `system` (else the workspace default) with its
+/// default parameters, seed 0, unmetered and with encoding 0.
+pub fn legacy(
+    cfg: &Config,
+    agent: &str,
+    root: [u8; 32],
+    system: Option<String>,
+) -> Commitment {
+    let plugin = systems::resolve(&cfg.system(system));
+    Commitment {
+        system: plugin.name().into(),
+        agent: agent.to_string(),
+        merkle_root: hex::encode(root),
+        system_params: plugin.default_params(),
+        seed: 0,
+        limits: Limits::unmetered(),
+        encoding: 0,
+        lineup: Vec::new(),
+        steps: Vec::new(),
+        matches: Vec::new(),
+    }
+}
+
+/// Opponent wasm from the workspace's agents directory.
+pub fn workspace_wasm(cfg: &Config) -> impl Fn(&str) -> Result<Vec<u8>, String> + '_ {
+    |id| {
+        let path = cfg.agent_wasm(id);
+        fs::read(&path).map_err(|_| format!("opponent wasm not found: {:?}", path))
+    }
+}
+
+/* ------------------------------
+   Committed run metadata
+-------------------------------*/
+
+const RECORDS_KEY: &str = "commitment_records";
+
+/// Full commitments (system + params) for the roots nets-core persists,
+/// so verify and export replay exactly what was committed.
+pub fn load_records(path: &str) -> BTreeMap<String, Commitment> {
+    state_file::load_section(path, RECORDS_KEY)
+}
+
+pub fn save_records(path: &str, records: &BTreeMap<String, Commitment>) {
+    state_file::save_section(path, RECORDS_KEY, records);
+}
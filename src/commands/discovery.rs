@@ -0,0 +1,195 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Serialize, Deserialize};
+
+use crate::systems;
+
+/* ------------------------------
+   Agent discovery
+-------------------------------*/
+
+/// Guest ABI version this CLI's adapters speak.
+pub const AGENT_ABI_VERSION: u32 = 2;
+
+/// Oldest guest ABI still accepted; 2 only added `NETS_OBS`, which some
+/// systems require (`SystemPlugin::min_abi`).
+pub const MIN_AGENT_ABI_VERSION: u32 = 1;
+
+/// Optional `<agent>.toml` next to `<agent>.wasm`. Every key is optional.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AgentManifest {
+    pub display_name: Option<String>,
+    /// Wallet credited with this agent's rewards.
+    pub owner_wallet: Option<String>,
+    /// Systems the agent can play; empty means any.
+    pub systems: Vec<String>,
+    /// Guest ABI the agent was built against; absent means the current one.
+    pub abi_version: Option<u32>,
+    pub tags: Vec<String>,
+    pub description: Option<String>,
+}
+
+impl AgentManifest {
+    pub fn supports(&self, system: &str) -> bool {
+        self.systems.is_empty() || self.systems.iter().any(|s| s == system)
+    }
+
+    /// Guest ABI the agent was built against.
+    pub fn abi(&self) -> u32 {
+        self.abi_version.unwrap_or(AGENT_ABI_VERSION)
+    }
+
+    pub fn abi_supported(&self) -> bool {
+        (MIN_AGENT_ABI_VERSION..=AGENT_ABI_VERSION).contains(&self.abi())
+    }
+}
+
+pub struct DiscoveredAgent {
+    pub id: String,
+    pub wasm_path: PathBuf,
+    /// The defaults when the manifest is missing or invalid.
+    pub manifest: AgentManifest,
+    /// Why `<agent>.toml` could not be read; such agents are skipped.
+    pub manifest_error: Option<String>,
+}
+
+impl DiscoveredAgent {
+    /// Display name from the manifest, else the id.
+    pub fn name(&self) -> &str {
+        self.manifest.display_name.as_deref().unwrap_or(&self.id)
+    }
+}
+
+/// Every `*.wasm` in `dir` with its manifest, sorted by id so runs do not
+/// depend on directory order.
+pub fn discover(dir: &str) -> Vec<DiscoveredAgent> {
+    let mut out = Vec::new();
+
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("wasm") {
+            continue;
+        }
+
+        let id = path.file_stem().unwrap().to_string_lossy().to_string();
+        let (manifest, manifest_error) = match load_manifest(&path.with_extension("toml")) {
+            Ok(manifest) => (manifest, None),
+            Err(e) => (AgentManifest::default(), Some(e)),
+        };
+        out.push(DiscoveredAgent { id, wasm_path: path, manifest, manifest_error });
+    }
+
+    out.sort_by(|a, b| a.id.cmp(&b.id));
+    out
+}
+
+/// An invalid manifest only affects its own agent, so it is reported
+/// rather than stopping discovery. A misspelled system name is invalid
+/// too, rather than quietly keeping the agent out of that system's runs.
+fn load_manifest(path: &Path) -> Result<AgentManifest, String> {
+    let manifest: AgentManifest = match fs::read_to_string(path) {
+        Ok(s) => toml::from_str(&s)
+            .map_err(|e| format!("invalid manifest {}: {}", path.display(), e))?,
+        Err(_) => return Ok(AgentManifest::default()),
+    };
+
+    if let Some(name) = manifest.systems.iter().find(|s| systems::lookup(s).is_none()) {
+        return Err(format!(
+            "invalid manifest {}: unknown system '{}' (known: {})",
+            path.display(),
+            name,
+            systems::names().join(", ")
+        ));
+    }
+    Ok(manifest)
+}
+
+/* ------------------------------
+   Selection
+-------------------------------*/
+
+/// Which discovered agents take part in a run.
+///
+/// `include` and `exclude` hold ids or glob patterns (`*`, `?`). An agent
+/// is selected when it matches `include` (or `include` is empty), matches
+/// nothing in `exclude`, and carries every tag in `tags`.
+#[derive(Default)]
+pub struct Selection {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub tags: Vec<String>,
+}
+
+impl Selection {
+    pub fn matches(&self, agent: &DiscoveredAgent) -> bool {
+        let included = self.include.is_empty()
+            || self.include.iter().any(|p| glob_match(p, &agent.id));
+        let excluded = self.exclude.iter().any(|p| glob_match(p, &agent.id));
+        let tagged = self.tags.iter().all(|t| agent.manifest.tags.contains(t));
+
+        included && !excluded && tagged
+    }
+
+    /// Exact ids in `include` that matched no discovered agent.
+    pub fn missing(&self, agents: &[DiscoveredAgent]) -> Vec<String> {
+        self.include
+            .iter()
+            .filter(|p| !p.contains(['*', '?']))
+            .filter(|p| !agents.iter().any(|a| &a.id == *p))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Glob match supporting `*` (any run) and `?` (any one character).
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_system_names_invalidate_the_manifest() {
+        let dir = std::env::temp_dir().join(format!("nets-discovery-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for id in ["good", "typo"] {
+            fs::write(dir.join(format!("{}.wasm", id)), b"").unwrap();
+        }
+        fs::write(dir.join("good.toml"), r#"systems = ["snake", "rps"]"#).unwrap();
+        fs::write(dir.join("typo.toml"), r#"systems = ["snake", "snak"]"#).unwrap();
+
+        let agents = discover(dir.to_str().unwrap());
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(agents[0].manifest_error.is_none());
+        assert_eq!(agents[0].manifest.systems, ["snake", "rps"]);
+        let error = agents[1].manifest_error.as_deref().unwrap();
+        assert!(error.contains("unknown system 'snak'"), "{}", error);
+    }
+}
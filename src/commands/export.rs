@@ -1,13 +1,14 @@
 use std::fs;
 
 use nets::{persist, ledger::Ledger, league_state::LeagueState};
-use crate::commands::commitment::{Commitment, SystemParams};
+use crate::commands::commitment;
+use crate::config::Config;
 
-pub fn export(agent: String, out: String) {
+pub fn export(cfg: &Config, agent: String, system: Option<String>, out: String) {
     let mut ledger = Ledger::new();
     let mut league_state = LeagueState::default();
 
-    let state = persist::load("state.json", &mut ledger, &mut league_state);
+    let state = persist::load(&cfg.state_path, &mut ledger, &mut league_state);
 
     let root = state.commitments
         .iter()
@@ -18,16 +19,8 @@ pub fn export(agent: String, out: String) {
             std::process::exit(1);
         });
 
-    let commitment = Commitment {
-        system: "snake".into(),
-        agent: agent.clone(),
-        merkle_root: hex::encode(root),
-        system_params: SystemParams {
-            width: 10,
-            height: 10,
-            steps: 300,
-        },
-    };
+    // Older state files only carry the root; assume the system defaults.
+    let commitment = commitment::for_root(cfg, &agent, root, system);
 
     fs::write(&out, serde_json::to_string_pretty(&commitment).unwrap()).unwrap();
     println!("commitment written to {}", out);
@@ -1,8 +1,11 @@
 use nets::{
-    match_trace::MatchTrace,
+    match_trace::{MatchTrace, TraceStep},
+    merkle::MerkleTree,
     fraud::TraceStepProof,
 };
 
+use crate::commands::trace_file::TraceFileStep;
+
 /// Find the first step where two traces diverge.
 /// Returns None if traces are identical.
 pub fn first_divergent_step(a: &MatchTrace, b: &MatchTrace) -> Option<usize> {
@@ -21,15 +24,18 @@ pub fn first_divergent_step(a: &MatchTrace, b: &MatchTrace) -> Option<usize> {
     None
 }
 
-/// Build a TraceStepProof for the first divergent step.
-/// Returns None if no divergence exists.
+/// Build a TraceStepProof for the first committed step that the
+/// recomputed trace disagrees with. The proof carries the committed step,
+/// since that is the one its Merkle path proves against the committed root.
+/// Returns None if no divergence exists, or if the committed trace is a
+/// strict prefix of the recomputed one and so has no step to point at.
 pub fn build_first_divergent_proof(
     committed: &MatchTrace,
     recomputed: &MatchTrace,
 ) -> Option<TraceStepProof> {
     let idx = first_divergent_step(committed, recomputed)?;
 
-    let step = &recomputed.steps[idx];
+    let step = committed.steps.get(idx)?;
 
     Some(TraceStepProof {
         step_index: step.step,
@@ -38,3 +44,71 @@ pub fn build_first_divergent_proof(
         merkle_path: committed.step_proof(idx),
     })
 }
+
+/// Rebuild a committed trace from the step hashes stored with it.
+pub fn trace_from_steps(steps: &[TraceFileStep]) -> Result<MatchTrace, String> {
+    let steps = steps
+        .iter()
+        .map(|s| {
+            Ok(TraceStep {
+                step: s.step,
+                obs_hash: decode_hash(&s.obs_hash)?,
+                action_hash: decode_hash(&s.action_hash)?,
+            })
+        })
+        .collect::<Result<Vec<TraceStep>, String>>()?;
+
+    let leaves = steps.iter().map(TraceStep::hash).collect();
+    Ok(MatchTrace { merkle: MerkleTree::from_leaves(leaves), steps })
+}
+
+fn decode_hash(s: &str) -> Result<[u8; 32], String> {
+    hex::decode(s)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| format!("invalid step hash '{}'", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use nets::fraud::verify_step_proof;
+
+    use super::*;
+
+    fn steps(actions: &[u8]) -> Vec<TraceFileStep> {
+        actions
+            .iter()
+            .enumerate()
+            .map(|(i, &a)| TraceFileStep {
+                step: i as u64,
+                obs_hash: hex::encode([i as u8; 32]),
+                action_hash: hex::encode([a; 32]),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn an_honest_commitment_has_no_proof() {
+        let honest = trace_from_steps(&steps(&[1, 2, 3])).unwrap();
+        assert!(build_first_divergent_proof(&honest, &honest).is_none());
+    }
+
+    #[test]
+    fn a_tampered_commitment_yields_a_proof_of_the_committed_step() {
+        let honest = trace_from_steps(&steps(&[1, 2, 3, 4])).unwrap();
+        let tampered = trace_from_steps(&steps(&[1, 2, 9, 4])).unwrap();
+
+        let proof = build_first_divergent_proof(&tampered, &honest).unwrap();
+        assert_eq!(proof.step_index, 2);
+        assert_eq!(proof.action_hash, [9; 32]);
+        assert!(verify_step_proof(tampered.merkle.root(), &proof));
+        assert!(!verify_step_proof(honest.merkle.root(), &proof));
+    }
+
+    #[test]
+    fn step_hashes_must_be_32_bytes_of_hex() {
+        let mut bad = steps(&[1]);
+        bad[0].obs_hash = "abcd".into();
+        assert!(trace_from_steps(&bad).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+use std::fs;
+use std::path::Path;
+
+use nets::{ledger::Ledger, league_state::LeagueState, match_trace::MatchTrace, persist};
+
+use crate::cli::MatchArgs;
+use crate::commands::{run::resolve_params, trace_file::TraceFile};
+use crate::config::Config;
+use crate::systems::{self, guest::Module, match_seed};
+
+/* ------------------------------
+   nets match
+-------------------------------*/
+
+/// Play `a` against `b` on the same seeded instances, or on one shared
+/// board for multi-agent systems; the higher score wins each game. Never
+/// writes to state.
+pub fn head_to_head(cfg: &Config, args: MatchArgs) {
+    let plugin = systems::resolve(&cfg.system(args.system));
+    let params = resolve_params(cfg, plugin, args.params, args.params_file);
+    let league = args.league.unwrap_or(cfg.league).league();
+    let limits = args.limits.apply(cfg.limits);
+
+    let a = load(&args.a);
+    let b = load(&args.b);
+
+    // Scoring may depend on capacity, so read the ledger, but never save it.
+    let mut ledger = Ledger::new();
+    let mut league_state = LeagueState::default();
+    persist::load(&cfg.state_path, &mut ledger, &mut league_state);
+
+    if let Some(dir) = &args.out_dir {
+        fs::create_dir_all(dir).unwrap_or_else(|_| {
+            eprintln!("cannot create output directory: {}", dir);
+            std::process::exit(1);
+        });
+    }
+
+    // Each agent is compiled once and instantiated for every game.
+    let compile = |(id, wasm): &(String, Vec<u8>)| {
+        Module::compile(wasm).unwrap_or_else(|e| {
+            eprintln!("cannot load agent {}: {}", id, e);
+            std::process::exit(1);
+        })
+    };
+    let modules = (compile(&a), compile(&b));
+
+    let (mut wins, mut draws, mut losses) = (0, 0, 0);
+    let (mut total_a, mut total_b) = (0i64, 0i64);
+
+    println!("{} vs {} system={}", a.0, b.0, plugin.name());
+    let shared = plugin.seats(&params) > 1;
+
+    for game in 0..args.games {
+        let seed = match_seed(args.seed, game as u64);
+
+        // On shared boards both agents play the same match.
+        let lineup = [(a.0.as_str(), &modules.0), (b.0.as_str(), &modules.1)];
+        let mut played = plugin.play(&params, seed, &lineup, &ledger, &league, &limits);
+        let played_b = played.pop().unwrap();
+        let played_a = played.pop().unwrap();
+
+        // A disqualified agent loses the game; two of them draw it.
+        let order = match (&played_a, &played_b) {
+            (Ok((sa, _)), Ok((sb, _))) => sa.cmp(sb),
+            (Ok(_), Err(_)) => std::cmp::Ordering::Greater,
+            (Err(_), Ok(_)) => std::cmp::Ordering::Less,
+            (Err(_), Err(_)) => std::cmp::Ordering::Equal,
+        };
+        let winner = match order {
+            std::cmp::Ordering::Greater => {
+                wins += 1;
+                a.0.as_str()
+            }
+            std::cmp::Ordering::Less => {
+                losses += 1;
+                b.0.as_str()
+            }
+            std::cmp::Ordering::Equal => {
+                draws += 1;
+                "draw"
+            }
+        };
+
+        println!(
+            "game {} seed={} {}={} {}={} winner={}",
+            game + 1,
+            seed,
+            a.0,
+            score_text(&played_a),
+            b.0,
+            score_text(&played_b),
+            winner
+        );
+
+        for (side, id, played, total) in [
+            ("a", &a.0, &played_a, &mut total_a),
+            ("b", &b.0, &played_b, &mut total_b),
+        ] {
+            let Ok((score, trace)) = played else {
+                continue;
+            };
+            *total += score;
+
+            if let Some(dir) = &args.out_dir {
+                let mut file = TraceFile::new(plugin.name(), id, &params, seed, &limits, trace);
+                if shared {
+                    // A disqualified agent was not at the table that was scored.
+                    file.lineup = [(&a.0, &played_a), (&b.0, &played_b)]
+                        .into_iter()
+                        .filter(|(_, p)| p.is_ok())
+                        .map(|(id, _)| id.clone())
+                        .collect();
+                }
+                let base = Path::new(dir).join(format!("game{}_{}", game + 1, side));
+                write_json(&base.with_extension("trace.json"), &file);
+                write_json(&base.with_extension("commitment.json"), &file.commitment());
+            }
+        }
+    }
+
+    println!("\nsummary:");
+    println!(
+        "{} wins={} draws={} losses={} total_score={}",
+        a.0, wins, draws, losses, total_a
+    );
+    println!(
+        "{} wins={} draws={} losses={} total_score={}",
+        b.0, losses, draws, wins, total_b
+    );
+
+    if let Some(dir) = &args.out_dir {
+        println!("\ntraces + commitments written to {}", dir);
+    }
+}
+
+fn score_text(played: &Result<(i64, MatchTrace), String>) -> String {
+    match played {
+        Ok((score, _)) => score.to_string(),
+        Err(reason) => format!("disqualified ({})", reason),
+    }
+}
+
+/// Agent id (the file stem) and wasm of `path`.
+pub fn load(path: &str) -> (String, Vec<u8>) {
+    let wasm = fs::read(path).unwrap_or_else(|_| {
+        eprintln!("agent wasm not found: {}", path);
+        std::process::exit(1);
+    });
+    let id = Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string());
+    (id, wasm)
+}
+
+fn write_json<T: serde::Serialize>(path: &Path, value: &T) {
+    fs::write(path, serde_json::to_string_pretty(value).unwrap()).unwrap();
+}
@@ -0,0 +1,163 @@
+use serde::Serialize;
+
+use crate::cli::HistoryCommand;
+use crate::commands::output::OutputFormat;
+use crate::commands::run_record::{self, RunRecord};
+use crate::config::Config;
+
+/* ------------------------------
+   nets history
+-------------------------------*/
+
+pub fn handle(cfg: &Config, cmd: HistoryCommand, format: OutputFormat) {
+    match cmd {
+        HistoryCommand::List { system, agent, limit } => {
+            list(cfg, system, agent, limit, format)
+        }
+        HistoryCommand::Show { run_id } => show(cfg, run_id, format),
+    }
+}
+
+/// `nets history list` JSON output, oldest run first.
+#[derive(Serialize)]
+pub struct HistoryList<'a> {
+    pub runs: &'a [RunRecord],
+}
+
+fn list(
+    cfg: &Config,
+    system: Option<String>,
+    agent: Option<String>,
+    limit: Option<usize>,
+    format: OutputFormat,
+) {
+    let mut runs: Vec<RunRecord> = run_record::list(&cfg.history_dir)
+        .into_iter()
+        .filter(|r| !matches!(&system, Some(s) if &r.system != s))
+        .filter(|r| !matches!(&agent, Some(a) if r.result(a).is_none()))
+        .collect();
+
+    if let Some(limit) = limit {
+        let skip = runs.len().saturating_sub(limit);
+        runs.drain(..skip);
+    }
+
+    if !format.is_text() {
+        format.emit("history", &HistoryList { runs: &runs });
+        return;
+    }
+
+    if runs.is_empty() {
+        println!("no runs recorded in {}", cfg.history_dir);
+        return;
+    }
+
+    for r in &runs {
+        let summary = match &agent {
+            Some(a) => {
+                let res = r.result(a).unwrap();
+                format!("{} rank={} total_score={} reward={}", a, res.rank, res.total_score, res.reward)
+            }
+            None => format!(
+                "agents={} winner={}",
+                r.results.len(),
+                r.results.first().map(|w| w.agent.as_str()).unwrap_or("-")
+            ),
+        };
+
+        println!(
+            "{} {} {} {} {}{} {}",
+            r.id,
+            format_time(r.started_at),
+            r.system,
+            r.league,
+            r.format,
+            if r.committed { "" } else { " (dry)" },
+            summary
+        );
+    }
+}
+
+fn show(cfg: &Config, run_id: String, format: OutputFormat) {
+    let r = run_record::load(&cfg.history_dir, &run_id).unwrap_or_else(|| {
+        eprintln!("run not found: {}", run_id);
+        std::process::exit(1);
+    });
+
+    if !format.is_text() {
+        format.emit("run", &r);
+        return;
+    }
+
+    println!("run {}", r.id);
+    println!("started_at={}", format_time(r.started_at));
+    println!("committed={}", r.committed);
+    println!("nets_core_version={} cli_version={}", r.nets_core_version, r.cli_version);
+    println!("system={} params={}", r.system, r.system_params);
+    println!("seed={} league={} format={} payout={}", r.seed, r.league, r.format, r.payout);
+
+    println!("\nresults:");
+    for res in &r.results {
+        println!(
+            "{} rank={} total_score={} matches={} reward={}",
+            res.agent, res.rank, res.total_score, res.matches, res.reward
+        );
+    }
+
+    if !r.pairings.is_empty() {
+        println!("\npairings:");
+        for p in &r.pairings {
+            println!(
+                "round {} {}: {} {} vs {} {} -> {}",
+                p.round,
+                p.bracket,
+                p.a,
+                p.score_a,
+                p.b.as_deref().unwrap_or("bye"),
+                p.score_b,
+                p.winner.as_deref().unwrap_or("draw")
+            );
+        }
+    }
+
+    if !r.disqualified.is_empty() {
+        println!("\ndisqualified:");
+        for d in &r.disqualified {
+            println!("{}: {}", d.agent, d.reason);
+        }
+    }
+
+    println!("\ncommitments:");
+    for c in &r.commitments {
+        println!("{} merkle_root={} seed={}", c.agent, c.merkle_root, c.seed);
+        for (i, m) in c.matches.iter().enumerate() {
+            println!("  match {} seed={} merkle_root={}", i, m.seed, m.merkle_root);
+        }
+    }
+}
+
+/// `YYYY-MM-DD HH:MM UTC` from Unix seconds.
+pub fn format_time(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Civil-from-days (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60
+    )
+}
@@ -0,0 +1,250 @@
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use nets::{ledger::Ledger, league_state::LeagueState, evolution::evolve, persist};
+
+use crate::commands::output::OutputFormat;
+use crate::commands::ratings::{Rating, RatingBook};
+use crate::commands::run_record::{self, RunRecord};
+use crate::config::Config;
+use crate::wallet::mock::MockWalletAdapter;
+
+/* ------------------------------
+   nets leaderboard
+-------------------------------*/
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortKey {
+    #[default]
+    Balance,
+    Rating,
+    Wins,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "balance" => Ok(SortKey::Balance),
+            "rating" => Ok(SortKey::Rating),
+            "wins" => Ok(SortKey::Wins),
+            _ => Err(format!("unknown sort key '{}' (known: balance, rating, wins)", s)),
+        }
+    }
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SortKey::Balance => "balance",
+            SortKey::Rating => "rating",
+            SortKey::Wins => "wins",
+        };
+        f.write_str(name)
+    }
+}
+
+/// One leaderboard row; also the `--format json` shape.
+#[derive(Serialize)]
+pub struct Row {
+    pub rank: usize,
+    pub agent: String,
+    pub wallet: Option<String>,
+    pub balance: u64,
+    pub capacity: u64,
+    pub rating: f64,
+    /// Committed runs of the system this agent finished first in.
+    pub wins: u64,
+    /// Committed runs of the system this agent took part in.
+    pub runs: u64,
+    /// Places gained (positive) or lost since before the latest committed
+    /// run of the system; absent when there is no earlier standing.
+    pub change: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct Leaderboard {
+    pub system: String,
+    pub sort: String,
+    /// Latest committed run the rank change is measured against.
+    pub last_run: Option<String>,
+    pub rows: Vec<Row>,
+}
+
+/// Sortable figures for one agent at one point in time.
+struct Figures {
+    balance: u64,
+    rating: f64,
+    wins: u64,
+}
+
+pub fn show(cfg: &Config, system: Option<String>, sort: SortKey, format: OutputFormat) {
+    let system = cfg.system(system);
+
+    let mut ledger = Ledger::new();
+    let mut league_state = LeagueState::default();
+    let _ = persist::load(&cfg.state_path, &mut ledger, &mut league_state);
+
+    let wallets = MockWalletAdapter::load(&cfg.state_path);
+    let book = RatingBook::load(&cfg.state_path);
+    let ratings = book.system(&system);
+
+    let runs: Vec<RunRecord> = run_record::list(&cfg.history_dir)
+        .into_iter()
+        .filter(|r| r.committed && r.system == system)
+        .collect();
+    let last = runs.last();
+
+    let mut agents: BTreeSet<String> = ledger
+        .snapshot()
+        .into_iter()
+        .map(|(id, _)| id.to_string())
+        .collect();
+    if let Some(r) = ratings {
+        agents.extend(r.agents.keys().cloned());
+    }
+    for run in &runs {
+        agents.extend(run.results.iter().map(|res| res.agent.clone()));
+    }
+
+    let now: Vec<Figures> = agents
+        .iter()
+        .map(|id| Figures {
+            balance: ledger.balance(id),
+            rating: ratings
+                .and_then(|r| r.agents.get(id))
+                .map(|r| r.rating)
+                .unwrap_or(Rating::default().rating),
+            wins: wins(&runs, id),
+        })
+        .collect();
+
+    // Undo the latest run to rank the board as it stood before it.
+    let before: Option<Vec<Figures>> = last.map(|last| {
+        let period = ratings.map(|r| r.periods).unwrap_or(0);
+        agents
+            .iter()
+            .zip(&now)
+            .map(|(id, s)| {
+                let res = last.result(id);
+                let rating = ratings
+                    .and_then(|r| {
+                        r.history
+                            .iter()
+                            .rev()
+                            .find(|h| &h.agent == id && h.period < period)
+                    })
+                    .map(|h| h.rating)
+                    .unwrap_or(if res.is_some() { Rating::default().rating } else { s.rating });
+                Figures {
+                    balance: s.balance.saturating_sub(res.map(|r| r.reward).unwrap_or(0)),
+                    rating,
+                    wins: s.wins - u64::from(res.map(|r| r.rank == 1).unwrap_or(false)),
+                }
+            })
+            .collect()
+    });
+
+    let ids: Vec<&String> = agents.iter().collect();
+    let ranks = rank(&ids, &now, sort);
+    let prev = before.as_ref().map(|b| rank(&ids, b, sort));
+
+    let mut rows: Vec<Row> = ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| Row {
+            rank: ranks[i],
+            agent: id.to_string(),
+            wallet: wallets.wallet_of(id).map(String::from),
+            balance: now[i].balance,
+            capacity: evolve(&ledger, id) as u64,
+            rating: now[i].rating,
+            wins: now[i].wins,
+            runs: runs.iter().filter(|r| r.result(id).is_some()).count() as u64,
+            change: prev.as_ref().map(|p| p[i] as i64 - ranks[i] as i64),
+        })
+        .collect();
+    rows.sort_by_key(|r| r.rank);
+
+    let board = Leaderboard {
+        system,
+        sort: sort.to_string(),
+        last_run: last.map(|r| r.id.clone()),
+        rows,
+    };
+
+    if format.is_text() {
+        print(&board);
+    }
+    format.emit("leaderboard", &board);
+}
+
+fn wins(runs: &[RunRecord], agent: &str) -> u64 {
+    runs.iter()
+        .filter(|r| r.results.first().map(|w| w.agent == agent).unwrap_or(false))
+        .count() as u64
+}
+
+/// 1-based rank of each agent under `sort`; ties break by agent id.
+fn rank(ids: &[&String], standings: &[Figures], sort: SortKey) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..ids.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (x, y) = (&standings[a], &standings[b]);
+        let key = match sort {
+            SortKey::Balance => y.balance.cmp(&x.balance),
+            SortKey::Rating => y.rating.total_cmp(&x.rating),
+            SortKey::Wins => y.wins.cmp(&x.wins),
+        };
+        match key {
+            Ordering::Equal => ids[a].cmp(ids[b]),
+            other => other,
+        }
+    });
+
+    let mut ranks = vec![0; ids.len()];
+    for (place, i) in order.into_iter().enumerate() {
+        ranks[i] = place + 1;
+    }
+    ranks
+}
+
+fn print(board: &Leaderboard) {
+    if board.rows.is_empty() {
+        println!("no agents for system {}", board.system);
+        return;
+    }
+
+    println!(
+        "{} leaderboard by {} (changes since {}):",
+        board.system,
+        board.sort,
+        board.last_run.as_deref().unwrap_or("-")
+    );
+    println!(
+        "{:>4} {:>4}  {:<20} {:<12} {:>8} {:>8} {:>7} {:>4} {:>4}",
+        "rank", "+/-", "agent", "wallet", "balance", "capacity", "rating", "wins", "runs"
+    );
+    for r in &board.rows {
+        let change = match r.change {
+            Some(0) | None => "-".to_string(),
+            Some(c) => format!("{:+}", c),
+        };
+        println!(
+            "{:>4} {:>4}  {:<20} {:<12} {:>8} {:>8} {:>7.1} {:>4} {:>4}",
+            r.rank,
+            change,
+            r.agent,
+            r.wallet.as_deref().unwrap_or("-"),
+            r.balance,
+            r.capacity,
+            r.rating,
+            r.wins,
+            r.runs
+        );
+    }
+}
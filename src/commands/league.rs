@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Serialize, Deserialize};
+
+use nets::{league::League, ledger::Ledger, league_state::LeagueState, evolution::evolve, persist};
+
+use crate::cli::LeagueCommand;
+use crate::commands::{discovery, output::OutputFormat, state_file};
+use crate::config::Config;
+use crate::systems::Standing;
+
+/* ------------------------------
+   Tiers
+-------------------------------*/
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tier {
+    Bronze,
+    Silver,
+    Gold,
+}
+
+impl Tier {
+    pub const ALL: [Tier; 3] = [Tier::Bronze, Tier::Silver, Tier::Gold];
+
+    pub fn league(self) -> League {
+        match self {
+            Tier::Bronze => League::bronze(),
+            Tier::Silver => League::silver(),
+            Tier::Gold => League::gold(),
+        }
+    }
+
+    fn up(self) -> Option<Tier> {
+        match self {
+            Tier::Bronze => Some(Tier::Silver),
+            Tier::Silver => Some(Tier::Gold),
+            Tier::Gold => None,
+        }
+    }
+
+    fn down(self) -> Option<Tier> {
+        match self {
+            Tier::Bronze => None,
+            Tier::Silver => Some(Tier::Bronze),
+            Tier::Gold => Some(Tier::Silver),
+        }
+    }
+}
+
+impl FromStr for Tier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "bronze" => Ok(Tier::Bronze),
+            "silver" => Ok(Tier::Silver),
+            "gold" => Ok(Tier::Gold),
+            _ => Err(format!("unknown league '{}' (known: bronze, silver, gold)", s)),
+        }
+    }
+}
+
+impl fmt::Display for Tier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Tier::Bronze => "bronze",
+            Tier::Silver => "silver",
+            Tier::Gold => "gold",
+        };
+        f.write_str(name)
+    }
+}
+
+/* ------------------------------
+   Membership
+-------------------------------*/
+
+/// Promotion and relegation rules, `[tiers]` in nets.toml.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TierRules {
+    /// Agents promoted from the top of each committed run.
+    pub promote: usize,
+    /// Agents relegated from the bottom of each committed run.
+    pub relegate: usize,
+    /// Capacity an agent needs to enter silver.
+    pub silver_capacity: u64,
+    /// Capacity an agent needs to enter gold.
+    pub gold_capacity: u64,
+}
+
+impl Default for TierRules {
+    fn default() -> Self {
+        Self { promote: 1, relegate: 1, silver_capacity: 0, gold_capacity: 0 }
+    }
+}
+
+impl TierRules {
+    fn entry_capacity(&self, tier: Tier) -> u64 {
+        match tier {
+            Tier::Bronze => 0,
+            Tier::Silver => self.silver_capacity,
+            Tier::Gold => self.gold_capacity,
+        }
+    }
+}
+
+const TIERS_KEY: &str = "tiers";
+
+/// Tier per agent; agents never seen by a committed run sit in bronze.
+#[derive(Default, Serialize, Deserialize)]
+pub struct TierMap(BTreeMap<String, Tier>);
+
+impl TierMap {
+    pub fn load(path: &str) -> Self {
+        state_file::load_section(path, TIERS_KEY)
+    }
+
+    pub fn save(&self, path: &str) {
+        state_file::save_section(path, TIERS_KEY, self);
+    }
+
+    pub fn tier(&self, agent: &str) -> Tier {
+        self.0.get(agent).copied().unwrap_or(Tier::Bronze)
+    }
+
+    /// Apply promotion and relegation for a committed run in `tier`.
+    ///
+    /// `ranked` is best-first and the ledger must already include the
+    /// run's rewards. Returns `(agent, from, to)` for every move.
+    pub fn apply(
+        &mut self,
+        tier: Tier,
+        ranked: &[Standing],
+        ledger: &Ledger,
+        rules: &TierRules,
+    ) -> Vec<(String, Tier, Tier)> {
+        let mut moves = Vec::new();
+        let n = ranked.len();
+
+        for r in ranked {
+            self.0.entry(r.agent_id.clone()).or_insert(tier);
+        }
+
+        if let Some(up) = tier.up() {
+            for r in ranked.iter().take(rules.promote.min(n)) {
+                if evolve(ledger, &r.agent_id) as u64 >= rules.entry_capacity(up) {
+                    moves.push((r.agent_id.clone(), tier, up));
+                }
+            }
+        }
+
+        if let Some(down) = tier.down() {
+            // Never relegate an agent that was just promoted.
+            let keep = rules.promote.min(n);
+            let relegate = rules.relegate.min(n - keep);
+            for r in ranked.iter().skip(n - relegate) {
+                moves.push((r.agent_id.clone(), tier, down));
+            }
+
+            for r in ranked.iter().take(n - relegate) {
+                let promoted = moves.iter().any(|(id, _, _)| id == &r.agent_id);
+                if !promoted && (evolve(ledger, &r.agent_id) as u64) < rules.entry_capacity(tier) {
+                    moves.push((r.agent_id.clone(), tier, down));
+                }
+            }
+        }
+
+        for (id, _, to) in &moves {
+            self.0.insert(id.clone(), *to);
+        }
+
+        moves
+    }
+}
+
+/* ------------------------------
+   nets league
+-------------------------------*/
+
+pub fn handle(cfg: &Config, cmd: LeagueCommand, format: OutputFormat) {
+    match cmd {
+        LeagueCommand::Status => status(cfg, format),
+    }
+}
+
+/// `nets league status` JSON output, highest tier first.
+#[derive(Serialize)]
+pub struct LeagueStatus {
+    pub tiers: Vec<TierStatus>,
+}
+
+#[derive(Serialize)]
+pub struct TierStatus {
+    pub tier: Tier,
+    pub agents: Vec<TierAgent>,
+}
+
+#[derive(Serialize)]
+pub struct TierAgent {
+    pub agent: String,
+    pub balance: u64,
+    pub capacity: u64,
+}
+
+fn status(cfg: &Config, format: OutputFormat) {
+    let mut ledger = Ledger::new();
+    let mut league_state = LeagueState::default();
+    let _ = persist::load(&cfg.state_path, &mut ledger, &mut league_state);
+
+    let tiers = TierMap::load(&cfg.state_path);
+
+    // Agents present on disk but never ranked still show up in bronze.
+    let mut agents: Vec<String> = tiers.0.keys().cloned().collect();
+    for agent in discovery::discover(&cfg.agents_dir) {
+        if !agents.contains(&agent.id) {
+            agents.push(agent.id);
+        }
+    }
+    agents.sort();
+
+    let mut report = LeagueStatus { tiers: Vec::new() };
+    for tier in Tier::ALL.iter().rev() {
+        let members: Vec<TierAgent> = agents
+            .iter()
+            .filter(|id| tiers.tier(id) == *tier)
+            .map(|id| TierAgent {
+                agent: id.clone(),
+                balance: ledger.balance(id),
+                capacity: evolve(&ledger, id) as u64,
+            })
+            .collect();
+
+        if format.is_text() {
+            println!("{}:", tier);
+            for a in &members {
+                println!("  {} balance={} capacity={}", a.agent, a.balance, a.capacity);
+            }
+        }
+        report.tiers.push(TierStatus { tier: *tier, agents: members });
+    }
+
+    format.emit("league_status", &report);
+}
@@ -1,4 +1,5 @@
 use crate::cli::{Cli, Command};
+use crate::config::Config;
 
 pub mod run;
 pub mod verify;
@@ -9,40 +10,76 @@ pub mod verify_fraud;
 pub mod slash;
 pub mod agent;
 pub mod balance;
-
-pub mod snake_agent;
-pub mod chess_agent;
-pub mod rps_agent;
+pub mod head_to_head;
+pub mod history;
+pub mod leaderboard;
+pub mod league;
+pub mod play;
+pub mod ratings;
+pub mod replay;
+pub mod trace;
 
 pub mod commitment;
+pub mod discovery;
 pub mod fraud_helpers;
+pub mod output;
+pub mod payout;
+pub mod pgn;
+pub mod plan;
+pub mod run_record;
+pub mod state_file;
+pub mod tournament;
+pub mod trace_file;
 
 pub fn dispatch(cli: Cli) {
+    let cfg = Config::load(cli.workspace, cli.config);
+    let format = cli.format;
+
     match cli.command {
-        Command::Run { system, matches, commit, wallet } => {
-            run::run(system, matches, commit, wallet);
-        }
+        Command::Run(args) => run::run(&cfg, args, format),
+
+        Command::Match(args) => head_to_head::head_to_head(&cfg, args),
 
-        Command::Verify { agent } => verify::verify(agent),
+        Command::Play(args) => play::play(&cfg, args),
 
-        Command::Export { agent, out } => export::export(agent, out),
+        Command::Replay(args) => replay::replay(&cfg, args),
+
+        Command::Verify { agent, system } => verify::verify(&cfg, agent, system, format),
+
+        Command::Export { agent, system, out } => {
+            export::export(&cfg, agent, system, out);
+        }
 
         Command::VerifyRemote { commitment, agent_wasm } => {
-            verify_remote::verify_remote(commitment, agent_wasm);
+            verify_remote::verify_remote(&cfg, commitment, agent_wasm, format);
         }
 
+        Command::Apply { plan } => plan::apply(&cfg, plan, format),
+
         Command::ProveFraud { commitment, agent_wasm, out, slash } => {
-            prove_fraud::prove_fraud(commitment, agent_wasm, out, slash);
+            prove_fraud::prove_fraud(&cfg, commitment, agent_wasm, out, slash, format);
         }
 
         Command::VerifyFraud { commitment, proof } => {
-            verify_fraud::verify_fraud(commitment, proof);
+            verify_fraud::verify_fraud(commitment, proof, format);
+        }
+
+        Command::Slash { agent, amount } => slash::slash(&cfg, agent, amount, format),
+
+        Command::Agent { action } => agent::handle(&cfg, action, format),
+
+        Command::League { action } => league::handle(&cfg, action, format),
+
+        Command::Ratings { system, agent } => ratings::show(&cfg, system, agent, format),
+
+        Command::Leaderboard { system, sort } => {
+            leaderboard::show(&cfg, system, sort, format);
         }
 
-        Command::Slash { agent, amount } => slash::slash(agent, amount),
+        Command::History { action } => history::handle(&cfg, action, format),
 
-        Command::Agent { action } => agent::handle(action),
+        Command::Trace { action } => trace::handle(&cfg, action),
 
-        Command::Balance { agent, wallet } => balance::show(agent, wallet),
+        Command::Balance { agent, wallet } => balance::show(&cfg, agent, wallet, format),
     }
 }
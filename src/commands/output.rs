@@ -0,0 +1,75 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+/* ------------------------------
+   Output formats
+-------------------------------*/
+
+/// `--format` for commands with machine-readable output.
+///
+/// `json` prints one document when the command finishes. `ndjson` prints
+/// one compact object per line, tagged with an `event` key; commands with
+/// a single result print just their final event. Errors always go to
+/// stderr as text, with a non-zero exit code.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Ndjson,
+}
+
+impl OutputFormat {
+    pub fn is_text(self) -> bool {
+        self == OutputFormat::Text
+    }
+
+    /// A streamed event; printed only in ndjson mode.
+    pub fn event<T: Serialize>(self, kind: &str, value: &T) {
+        if self != OutputFormat::Ndjson {
+            return;
+        }
+
+        let mut v = serde_json::to_value(value).unwrap();
+        if let serde_json::Value::Object(fields) = &mut v {
+            fields.insert("event".into(), kind.into());
+        }
+        println!("{}", v);
+    }
+
+    /// The command's final result: the json document, or the last ndjson
+    /// event. Text mode prints nothing; callers print their own text.
+    pub fn emit<T: Serialize>(self, kind: &str, value: &T) {
+        match self {
+            OutputFormat::Text => {}
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(value).unwrap()),
+            OutputFormat::Ndjson => self.event(kind, value),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "ndjson" => Ok(OutputFormat::Ndjson),
+            _ => Err(format!("unknown format '{}' (known: text, json, ndjson)", s)),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+            OutputFormat::Ndjson => "ndjson",
+        };
+        f.write_str(name)
+    }
+}
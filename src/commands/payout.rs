@@ -0,0 +1,244 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Serialize, Deserialize};
+
+use crate::systems::Standing;
+
+/* ------------------------------
+   Payout policies
+-------------------------------*/
+
+/// How rewards are distributed over a ranked result list.
+///
+/// Written on the command line as `<policy>:<args>`, e.g. `top-n:100,50`,
+/// and in `nets.toml` as a `[payout]` table with a `policy` key.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "policy", rename_all = "kebab-case")]
+pub enum PayoutPolicy {
+    /// `winner-take-all:<prize>`
+    WinnerTakeAll { prize: u64 },
+    /// `top-n:<r1>,<r2>,...`; ranks past the table earn 0.
+    TopN { table: Vec<u64> },
+    /// `proportional:<pool>`; pool split by (non-negative) score.
+    Proportional { pool: u64 },
+    /// `geometric:<first>,<keep_percent>`; each rank earns `keep_percent`
+    /// of the rank above it, until the reward reaches 0.
+    Geometric { first: u64, keep_percent: u64 },
+    /// `pool-split:<pool>:<w1>,<w2>,...`; pool split by rank weights.
+    PoolSplit { pool: u64, weights: Vec<u64> },
+}
+
+impl Default for PayoutPolicy {
+    fn default() -> Self {
+        PayoutPolicy::TopN { table: vec![100, 50] }
+    }
+}
+
+impl PayoutPolicy {
+    /// Rewards for `ranked`, which must already be sorted best-first.
+    pub fn payouts(&self, ranked: &[Standing]) -> Vec<u64> {
+        let n = ranked.len();
+
+        match self {
+            PayoutPolicy::WinnerTakeAll { prize } => (0..n)
+                .map(|rank| if rank == 0 { *prize } else { 0 })
+                .collect(),
+
+            PayoutPolicy::TopN { table } => (0..n)
+                .map(|rank| table.get(rank).copied().unwrap_or(0))
+                .collect(),
+
+            PayoutPolicy::Proportional { pool } => {
+                let scores: Vec<u128> = ranked
+                    .iter()
+                    .map(|r| r.total_score.max(0) as u128)
+                    .collect();
+                split(*pool, &scores)
+            }
+
+            PayoutPolicy::Geometric { first, keep_percent } => {
+                let keep = (*keep_percent).min(100) as u128;
+                let mut reward = *first;
+                (0..n)
+                    .map(|_| {
+                        let current = reward;
+                        reward = (reward as u128 * keep / 100) as u64;
+                        current
+                    })
+                    .collect()
+            }
+
+            PayoutPolicy::PoolSplit { pool, weights } => {
+                let weights: Vec<u128> = (0..n)
+                    .map(|rank| weights.get(rank).copied().unwrap_or(0) as u128)
+                    .collect();
+                split(*pool, &weights)
+            }
+        }
+    }
+}
+
+/// Split `pool` by `weights`, flooring each share. The rounding remainder
+/// goes to the first entries in order, so the whole pool is always paid
+/// out when any weight is positive.
+fn split(pool: u64, weights: &[u128]) -> Vec<u64> {
+    let total: u128 = weights.iter().sum();
+    if total == 0 {
+        return vec![0; weights.len()];
+    }
+
+    let mut out: Vec<u64> = weights
+        .iter()
+        .map(|w| (pool as u128 * w / total) as u64)
+        .collect();
+
+    let mut remainder = pool - out.iter().sum::<u64>();
+    for (i, w) in weights.iter().enumerate() {
+        if remainder == 0 {
+            break;
+        }
+        if *w > 0 {
+            out[i] += 1;
+            remainder -= 1;
+        }
+    }
+
+    out
+}
+
+/* ------------------------------
+   Text form
+-------------------------------*/
+
+fn parse_list(s: &str) -> Result<Vec<u64>, String> {
+    s.split(',')
+        .map(|v| v.trim().parse().map_err(|_| format!("invalid amount '{}'", v)))
+        .collect()
+}
+
+fn parse_one(s: &str) -> Result<u64, String> {
+    s.trim().parse().map_err(|_| format!("invalid amount '{}'", s))
+}
+
+fn join(values: &[u64]) -> String {
+    values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(",")
+}
+
+impl FromStr for PayoutPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (name, args) = s.split_once(':').unwrap_or((s, ""));
+
+        match name {
+            "winner-take-all" => Ok(PayoutPolicy::WinnerTakeAll { prize: parse_one(args)? }),
+            "top-n" => Ok(PayoutPolicy::TopN { table: parse_list(args)? }),
+            "proportional" => Ok(PayoutPolicy::Proportional { pool: parse_one(args)? }),
+            "geometric" => match parse_list(args)?.as_slice() {
+                [first, keep_percent] if *keep_percent <= 100 => Ok(PayoutPolicy::Geometric {
+                    first: *first,
+                    keep_percent: *keep_percent,
+                }),
+                _ => Err("geometric expects <first>,<keep_percent> with keep_percent <= 100".into()),
+            },
+            "pool-split" => {
+                let (pool, weights) = args
+                    .split_once(':')
+                    .ok_or("pool-split expects <pool>:<w1>,<w2>,...")?;
+                Ok(PayoutPolicy::PoolSplit {
+                    pool: parse_one(pool)?,
+                    weights: parse_list(weights)?,
+                })
+            }
+            _ => Err(format!(
+                "unknown payout policy '{}' (known: winner-take-all, top-n, proportional, geometric, pool-split)",
+                name
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PayoutPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayoutPolicy::WinnerTakeAll { prize } => write!(f, "winner-take-all:{}", prize),
+            PayoutPolicy::TopN { table } => write!(f, "top-n:{}", join(table)),
+            PayoutPolicy::Proportional { pool } => write!(f, "proportional:{}", pool),
+            PayoutPolicy::Geometric { first, keep_percent } => {
+                write!(f, "geometric:{},{}", first, keep_percent)
+            }
+            PayoutPolicy::PoolSplit { pool, weights } => {
+                write!(f, "pool-split:{}:{}", pool, join(weights))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranked(scores: &[i64]) -> Vec<Standing> {
+        scores
+            .iter()
+            .enumerate()
+            .map(|(i, &s)| Standing { agent_id: format!("a{}", i), total_score: s, matches: 1 })
+            .collect()
+    }
+
+    fn pay(policy: &str, scores: &[i64]) -> Vec<u64> {
+        policy.parse::<PayoutPolicy>().unwrap().payouts(&ranked(scores))
+    }
+
+    #[test]
+    fn winner_take_all_pays_first_only() {
+        assert_eq!(pay("winner-take-all:100", &[9, 5, 1]), vec![100, 0, 0]);
+    }
+
+    #[test]
+    fn top_n_pays_past_the_table_nothing() {
+        assert_eq!(pay("top-n:100,50", &[9, 5, 1]), vec![100, 50, 0]);
+        assert_eq!(pay("top-n:100,50,25", &[9]), vec![100]);
+    }
+
+    #[test]
+    fn proportional_gives_the_remainder_to_the_first_positive_scores() {
+        // 100 by 1:1:1 floors to 33 each; the leftover 1 goes to rank 1.
+        assert_eq!(pay("proportional:100", &[3, 3, 3]), vec![34, 33, 33]);
+        // Negative scores count as zero and never receive remainder.
+        assert_eq!(pay("proportional:10", &[-5, 1, 2]), vec![0, 4, 6]);
+    }
+
+    #[test]
+    fn proportional_with_no_positive_score_pays_nothing() {
+        assert_eq!(pay("proportional:100", &[0, -1]), vec![0, 0]);
+    }
+
+    #[test]
+    fn geometric_decays_until_zero() {
+        assert_eq!(pay("geometric:100,50", &[5, 4, 3, 2, 1]), vec![100, 50, 25, 12, 6]);
+        assert_eq!(pay("geometric:1,50", &[2, 1]), vec![1, 0]);
+    }
+
+    #[test]
+    fn pool_split_pays_out_the_whole_pool() {
+        let paid = pay("pool-split:100:3,2,1", &[3, 2, 1]);
+        assert_eq!(paid, vec![51, 33, 16]);
+        assert_eq!(paid.iter().sum::<u64>(), 100);
+        assert_eq!(pay("pool-split:10:1", &[3, 2]), vec![10, 0]);
+    }
+
+    #[test]
+    fn policies_round_trip_through_text() {
+        for s in ["winner-take-all:5", "top-n:3,2,1", "proportional:7", "geometric:9,80"] {
+            assert_eq!(s.parse::<PayoutPolicy>().unwrap().to_string(), s);
+        }
+        assert_eq!(
+            "pool-split:10:2,1".parse::<PayoutPolicy>().unwrap().to_string(),
+            "pool-split:10:2,1"
+        );
+        assert!("geometric:10,101".parse::<PayoutPolicy>().is_err());
+        assert!("fixed:1".parse::<PayoutPolicy>().is_err());
+    }
+}
@@ -0,0 +1,567 @@
+use std::fmt::Write as _;
+
+/* ------------------------------
+   Board model (SAN only)
+-------------------------------*/
+
+// Just enough chess to name moves: legal move generation, check and mate.
+// The rules themselves are nets-core's; positions come from its FENs.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Color {
+    White,
+    Black,
+}
+
+impl Color {
+    fn other(self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Piece {
+    color: Color,
+    /// Uppercase FEN letter: `P N B R Q K`.
+    kind: u8,
+}
+
+/// A move by square index (`a1` = 0, `h8` = 63).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Move {
+    pub from: u8,
+    pub to: u8,
+    /// Uppercase piece letter promoted to.
+    pub promotion: Option<u8>,
+}
+
+impl Move {
+    /// `e2e4`, `e7e8q`.
+    pub fn from_uci(uci: &str) -> Option<Move> {
+        let b = uci.as_bytes();
+        let promotion = match b.get(4) {
+            None => None,
+            Some(p @ (b'n' | b'b' | b'r' | b'q')) => Some(p.to_ascii_uppercase()),
+            Some(_) => return None,
+        };
+        if b.len() > 5 {
+            return None;
+        }
+        Some(Move { from: square(b.get(0..2)?)?, to: square(b.get(2..4)?)?, promotion })
+    }
+}
+
+fn square(name: &[u8]) -> Option<u8> {
+    match name {
+        [f @ b'a'..=b'h', r @ b'1'..=b'8'] => Some((r - b'1') * 8 + (f - b'a')),
+        _ => None,
+    }
+}
+
+fn square_name(sq: u8) -> String {
+    format!("{}{}", (b'a' + sq % 8) as char, (b'1' + sq / 8) as char)
+}
+
+/// Square `sq` moved by `(df, dr)`, if still on the board.
+fn offset(sq: u8, df: i8, dr: i8) -> Option<u8> {
+    let (f, r) = ((sq % 8) as i8 + df, (sq / 8) as i8 + dr);
+    ((0..8).contains(&f) && (0..8).contains(&r)).then(|| (r * 8 + f) as u8)
+}
+
+const KNIGHT: [(i8, i8); 8] =
+    [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+const KING: [(i8, i8); 8] =
+    [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+const ROOK: [(i8, i8); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const BISHOP: [(i8, i8); 4] = [(1, 1), (-1, 1), (-1, -1), (1, -1)];
+
+#[derive(Clone)]
+pub struct Board {
+    squares: [Option<Piece>; 64],
+    pub side: Color,
+    /// `K Q k q`.
+    castling: [bool; 4],
+    en_passant: Option<u8>,
+    pub fullmove: u32,
+}
+
+impl Board {
+    pub fn from_fen(fen: &str) -> Result<Board, String> {
+        let bad = || format!("invalid FEN: {}", fen);
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 {
+            return Err(bad());
+        }
+
+        let mut squares = [None; 64];
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err(bad());
+        }
+        for (i, rank) in ranks.iter().enumerate() {
+            let mut file = 0u8;
+            for c in rank.bytes() {
+                if c.is_ascii_digit() {
+                    file += c - b'0';
+                    continue;
+                }
+                if file > 7 || !b"PNBRQKpnbrqk".contains(&c) {
+                    return Err(bad());
+                }
+                let color = if c.is_ascii_uppercase() { Color::White } else { Color::Black };
+                squares[(7 - i) * 8 + file as usize] =
+                    Some(Piece { color, kind: c.to_ascii_uppercase() });
+                file += 1;
+            }
+            if file != 8 {
+                return Err(bad());
+            }
+        }
+
+        let side = match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            _ => return Err(bad()),
+        };
+        let castling = [
+            fields[2].contains('K'),
+            fields[2].contains('Q'),
+            fields[2].contains('k'),
+            fields[2].contains('q'),
+        ];
+        let en_passant = match fields[3] {
+            "-" => None,
+            sq => Some(square(sq.as_bytes()).ok_or_else(bad)?),
+        };
+        let fullmove = fields.get(5).and_then(|n| n.parse().ok()).unwrap_or(1);
+
+        Ok(Board { squares, side, castling, en_passant, fullmove })
+    }
+
+    /// Placement and side to move: what two positions must share to be
+    /// the same for move reconstruction.
+    pub fn key(&self) -> (Vec<Option<(Color, u8)>>, Color) {
+        (self.squares.iter().map(|p| p.map(|p| (p.color, p.kind))).collect(), self.side)
+    }
+
+    fn king(&self, color: Color) -> Option<u8> {
+        (0..64).find(|&sq| self.squares[sq as usize] == Some(Piece { color, kind: b'K' }))
+    }
+
+    fn attacked(&self, sq: u8, by: Color) -> bool {
+        let is = |s: Option<u8>, kinds: &[u8]| {
+            s.and_then(|s| self.squares[s as usize])
+                .map(|p| p.color == by && kinds.contains(&p.kind))
+                .unwrap_or(false)
+        };
+
+        let pawn_dr = if by == Color::White { -1 } else { 1 };
+        if is(offset(sq, -1, pawn_dr), b"P") || is(offset(sq, 1, pawn_dr), b"P") {
+            return true;
+        }
+        if KNIGHT.iter().any(|&(df, dr)| is(offset(sq, df, dr), b"N")) {
+            return true;
+        }
+        if KING.iter().any(|&(df, dr)| is(offset(sq, df, dr), b"K")) {
+            return true;
+        }
+        for (dirs, kinds) in [(&ROOK, b"RQ"), (&BISHOP, b"BQ")] {
+            for &(df, dr) in dirs {
+                let mut at = offset(sq, df, dr);
+                while let Some(s) = at {
+                    if let Some(p) = self.squares[s as usize] {
+                        if p.color == by && kinds.contains(&p.kind) {
+                            return true;
+                        }
+                        break;
+                    }
+                    at = offset(s, df, dr);
+                }
+            }
+        }
+        false
+    }
+
+    pub fn in_check(&self) -> bool {
+        self.king(self.side).map(|k| self.attacked(k, self.side.other())).unwrap_or(false)
+    }
+
+    fn pseudo_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        let side = self.side;
+        let free = |s: u8| self.squares[s as usize].is_none();
+        let enemy = |s: u8| self.squares[s as usize].map(|p| p.color != side).unwrap_or(false);
+
+        for from in 0..64u8 {
+            let Some(piece) = self.squares[from as usize].filter(|p| p.color == side) else {
+                continue;
+            };
+
+            match piece.kind {
+                b'P' => {
+                    let (dr, start, last) = if side == Color::White { (1, 1, 7) } else { (-1, 6, 0) };
+                    let mut targets = Vec::new();
+                    if let Some(one) = offset(from, 0, dr).filter(|&s| free(s)) {
+                        targets.push(one);
+                        if from / 8 == start {
+                            if let Some(two) = offset(one, 0, dr).filter(|&s| free(s)) {
+                                targets.push(two);
+                            }
+                        }
+                    }
+                    for df in [-1, 1] {
+                        if let Some(to) = offset(from, df, dr) {
+                            if enemy(to) || self.en_passant == Some(to) {
+                                targets.push(to);
+                            }
+                        }
+                    }
+                    for to in targets {
+                        if to / 8 == last {
+                            for p in *b"QRBN" {
+                                moves.push(Move { from, to, promotion: Some(p) });
+                            }
+                        } else {
+                            moves.push(Move { from, to, promotion: None });
+                        }
+                    }
+                }
+                b'N' | b'K' => {
+                    let steps = if piece.kind == b'N' { &KNIGHT } else { &KING };
+                    for &(df, dr) in steps {
+                        if let Some(to) = offset(from, df, dr).filter(|&s| free(s) || enemy(s)) {
+                            moves.push(Move { from, to, promotion: None });
+                        }
+                    }
+                }
+                kind => {
+                    let dirs: Vec<(i8, i8)> = match kind {
+                        b'R' => ROOK.to_vec(),
+                        b'B' => BISHOP.to_vec(),
+                        _ => ROOK.iter().chain(&BISHOP).copied().collect(),
+                    };
+                    for (df, dr) in dirs {
+                        let mut at = offset(from, df, dr);
+                        while let Some(to) = at {
+                            if free(to) {
+                                moves.push(Move { from, to, promotion: None });
+                                at = offset(to, df, dr);
+                                continue;
+                            }
+                            if enemy(to) {
+                                moves.push(Move { from, to, promotion: None });
+                            }
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Castling: rights, empty squares between, and no attacked square
+        // on the king's path.
+        let (rank, rights) = if side == Color::White { (0, [0, 1]) } else { (56, [2, 3]) };
+        if self.king(side) == Some(rank + 4) && !self.in_check() {
+            let them = side.other();
+            if self.castling[rights[0]]
+                && free(rank + 5)
+                && free(rank + 6)
+                && !self.attacked(rank + 5, them)
+                && !self.attacked(rank + 6, them)
+            {
+                moves.push(Move { from: rank + 4, to: rank + 6, promotion: None });
+            }
+            if self.castling[rights[1]]
+                && free(rank + 3)
+                && free(rank + 2)
+                && free(rank + 1)
+                && !self.attacked(rank + 3, them)
+                && !self.attacked(rank + 2, them)
+            {
+                moves.push(Move { from: rank + 4, to: rank + 2, promotion: None });
+            }
+        }
+
+        moves
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.pseudo_moves()
+            .into_iter()
+            .filter(|m| {
+                let mut after = self.clone();
+                after.apply(*m);
+                after.king(self.side).map(|k| !after.attacked(k, after.side)).unwrap_or(true)
+            })
+            .collect()
+    }
+
+    /// Play `m`, assumed legal.
+    pub fn apply(&mut self, m: Move) {
+        let Some(piece) = self.squares[m.from as usize] else {
+            return;
+        };
+        let (from, to) = (m.from as usize, m.to as usize);
+
+        // En passant removes the pawn beside the target square.
+        if piece.kind == b'P' && Some(m.to) == self.en_passant && self.squares[to].is_none() {
+            let captured = if piece.color == Color::White { to - 8 } else { to + 8 };
+            self.squares[captured] = None;
+        }
+        // Castling moves the rook too.
+        if piece.kind == b'K' && from.abs_diff(to) == 2 {
+            let (rook_from, rook_to) =
+                if to > from { (from + 3, from + 1) } else { (from - 4, from - 1) };
+            self.squares[rook_to] = self.squares[rook_from].take();
+        }
+
+        self.en_passant = (piece.kind == b'P' && from.abs_diff(to) == 16)
+            .then(|| ((from + to) / 2) as u8);
+
+        for (sq, right) in [(4, 0), (4, 1), (7, 0), (0, 1), (60, 2), (60, 3), (63, 2), (56, 3)] {
+            if from == sq || to == sq {
+                self.castling[right] = false;
+            }
+        }
+
+        self.squares[from] = None;
+        self.squares[to] = Some(match m.promotion {
+            Some(kind) => Piece { color: piece.color, kind },
+            None => piece,
+        });
+
+        if self.side == Color::Black {
+            self.fullmove += 1;
+        }
+        self.side = self.side.other();
+    }
+
+    /// Standard algebraic notation of legal move `m`, with `+` or `#`.
+    pub fn san(&self, m: Move) -> String {
+        let Some(piece) = self.squares[m.from as usize] else {
+            return square_name(m.from) + &square_name(m.to);
+        };
+        let capture = self.squares[m.to as usize].is_some()
+            || (piece.kind == b'P' && Some(m.to) == self.en_passant);
+
+        let mut san = String::new();
+        if piece.kind == b'K' && m.from.abs_diff(m.to) == 2 {
+            san.push_str(if m.to > m.from { "O-O" } else { "O-O-O" });
+        } else if piece.kind == b'P' {
+            if capture {
+                san.push((b'a' + m.from % 8) as char);
+                san.push('x');
+            }
+            san.push_str(&square_name(m.to));
+            if let Some(p) = m.promotion {
+                san.push('=');
+                san.push(p as char);
+            }
+        } else {
+            san.push(piece.kind as char);
+
+            // Disambiguate from other pieces of the same kind that could
+            // also reach the square: by file, else rank, else both.
+            let rivals: Vec<u8> = self
+                .legal_moves()
+                .into_iter()
+                .filter(|o| o.to == m.to && o.from != m.from)
+                .filter(|o| self.squares[o.from as usize].map(|p| p.kind) == Some(piece.kind))
+                .map(|o| o.from)
+                .collect();
+            if !rivals.is_empty() {
+                let name = square_name(m.from);
+                if rivals.iter().all(|r| r % 8 != m.from % 8) {
+                    san.push_str(&name[..1]);
+                } else if rivals.iter().all(|r| r / 8 != m.from / 8) {
+                    san.push_str(&name[1..]);
+                } else {
+                    san.push_str(&name);
+                }
+            }
+
+            if capture {
+                san.push('x');
+            }
+            san.push_str(&square_name(m.to));
+        }
+
+        let mut after = self.clone();
+        after.apply(m);
+        if after.in_check() {
+            san.push(if after.legal_moves().is_empty() { '#' } else { '+' });
+        }
+        san
+    }
+}
+
+/* ------------------------------
+   PGN text
+-------------------------------*/
+
+/// One ply of a game: its SAN and the comment after it, if any.
+pub struct Ply {
+    pub san: String,
+    pub comment: Option<String>,
+}
+
+/// A game to print: seven-tag roster plus extra tags, a comment before the
+/// first move, and the moves from `start`.
+pub struct Game {
+    pub tags: Vec<(String, String)>,
+    pub comment: Option<String>,
+    pub start: Board,
+    pub plies: Vec<Ply>,
+    pub result: String,
+}
+
+impl Game {
+    pub fn to_pgn(&self) -> String {
+        let mut out = String::new();
+        for (k, v) in &self.tags {
+            let _ = writeln!(out, "[{} \"{}\"]", k, v.replace('\\', "\\\\").replace('"', "\\\""));
+        }
+        out.push('\n');
+
+        let mut tokens: Vec<String> = Vec::new();
+        if let Some(c) = &self.comment {
+            tokens.push(format!("{{{}}}", c));
+        }
+
+        let (mut side, mut number) = (self.start.side, self.start.fullmove);
+        for (i, ply) in self.plies.iter().enumerate() {
+            match side {
+                Color::White => tokens.push(format!("{}.", number)),
+                Color::Black if i == 0 => tokens.push(format!("{}...", number)),
+                Color::Black => {}
+            }
+            tokens.push(ply.san.clone());
+            if let Some(c) = &ply.comment {
+                tokens.push(format!("{{{}}}", c));
+            }
+            if side == Color::Black {
+                number += 1;
+            }
+            side = side.other();
+        }
+        tokens.push(self.result.clone());
+
+        // Movetext lines stay under 80 characters.
+        let mut line = String::new();
+        for t in tokens {
+            if !line.is_empty() && line.len() + 1 + t.len() > 79 {
+                out.push_str(&line);
+                out.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&t);
+        }
+        out.push_str(&line);
+        out.push('\n');
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    fn san(fen: &str, uci: &str) -> String {
+        let board = Board::from_fen(fen).unwrap();
+        let m = Move::from_uci(uci).unwrap();
+        assert!(board.legal_moves().contains(&m), "{} is not legal in {}", uci, fen);
+        board.san(m)
+    }
+
+    fn game(fen: &str, moves: &[&str], result: &str) -> Game {
+        let start = Board::from_fen(fen).unwrap();
+        let mut board = start.clone();
+        let mut plies = Vec::new();
+        for uci in moves {
+            let m = Move::from_uci(uci).unwrap();
+            plies.push(Ply { san: board.san(m), comment: None });
+            board.apply(m);
+        }
+        let tags = vec![("Event".to_string(), "test".to_string())];
+        Game { tags, comment: None, start, plies, result: result.to_string() }
+    }
+
+    #[test]
+    fn uci_moves_parse_with_promotions() {
+        assert_eq!(Move::from_uci("e2e4"), Some(Move { from: 12, to: 28, promotion: None }));
+        assert_eq!(Move::from_uci("e7e8q"), Some(Move { from: 52, to: 60, promotion: Some(b'Q') }));
+        assert_eq!(Move::from_uci("e7e8k"), None);
+        assert_eq!(Move::from_uci("e2e9"), None);
+    }
+
+    #[test]
+    fn pieces_are_disambiguated_by_file_then_rank_then_both() {
+        assert_eq!(san("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1", "b1d2"), "Nbd2");
+        assert_eq!(san("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1", "a1a3"), "R1a3");
+        assert_eq!(san("4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1", "a1b2"), "Qa1b2");
+        assert_eq!(san(START, "g1f3"), "Nf3");
+    }
+
+    #[test]
+    fn castling_is_named_and_refused_through_attacked_squares() {
+        let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        assert_eq!(san(fen, "e1g1"), "O-O");
+        assert_eq!(san(fen, "e1c1"), "O-O-O");
+
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/5r2/R3K2R w KQkq - 0 1").unwrap();
+        let legal = board.legal_moves();
+        assert!(!legal.contains(&Move::from_uci("e1g1").unwrap()));
+        assert!(legal.contains(&Move::from_uci("e1c1").unwrap()));
+    }
+
+    #[test]
+    fn en_passant_captures_the_pawn_beside_the_target() {
+        let fen = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1";
+        assert_eq!(san(fen, "e5d6"), "exd6");
+
+        let mut board = Board::from_fen(fen).unwrap();
+        board.apply(Move::from_uci("e5d6").unwrap());
+        let want = Board::from_fen("4k3/8/3P4/8/8/8/8/4K3 b - - 0 1").unwrap();
+        assert!(board.key() == want.key());
+    }
+
+    #[test]
+    fn promotions_name_the_new_piece() {
+        assert_eq!(san("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8n"), "b8=N");
+        assert_eq!(san("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8q"), "b8=Q+");
+        assert_eq!(san("2r1k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7c8q"), "bxc8=Q+");
+    }
+
+    #[test]
+    fn checks_and_mates_are_suffixed() {
+        assert_eq!(san("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a8"), "Ra8+");
+        let fools = "rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq g3 0 2";
+        assert_eq!(san(fools, "d8h4"), "Qh4#");
+    }
+
+    #[test]
+    fn a_known_game_prints_as_pgn() {
+        let moves = ["e2e4", "e7e5", "d1h5", "b8c6", "f1c4", "g8f6", "h5f7"];
+        assert_eq!(
+            game(START, &moves, "1-0").to_pgn(),
+            "[Event \"test\"]\n\n1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0\n"
+        );
+    }
+
+    #[test]
+    fn a_game_starting_with_black_numbers_its_first_move() {
+        let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+        assert_eq!(
+            game(fen, &["e7e5", "g1f3"], "*").to_pgn(),
+            "[Event \"test\"]\n\n1... e5 2. Nf3 *\n"
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use serde::{Serialize, Deserialize};
+
+use crate::commands::{
+    commitment::Commitment,
+    league::Tier,
+    output::OutputFormat,
+    run::RunState,
+    run_record::{self, RunRecord},
+    state_file,
+};
+use crate::config::Config;
+use crate::wallet::adapter::WalletAdapter;
+
+/* ------------------------------
+   State diff
+-------------------------------*/
+
+/// What a run changes in state.json. Dry runs print it; `--plan-out`
+/// saves it with the exact file contents to write.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct StateDiff {
+    pub ledger: Vec<BalanceChange>,
+    pub wallets: Vec<WalletChange>,
+    pub bindings: Vec<BindingChange>,
+    pub commitments: Vec<CommitmentChange>,
+    pub tiers: Vec<TierMove>,
+    pub ratings: Vec<RatingChange>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BalanceChange {
+    pub agent: String,
+    pub before: u64,
+    pub after: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WalletChange {
+    pub wallet: String,
+    pub before: u64,
+    pub after: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BindingChange {
+    pub agent: String,
+    pub before: Option<String>,
+    pub after: String,
+}
+
+/// A new or replaced commitment; roots are hex.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CommitmentChange {
+    pub agent: String,
+    pub before: Option<String>,
+    pub after: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TierMove {
+    pub agent: String,
+    pub from: Tier,
+    pub to: Tier,
+}
+
+/// Glicko-2 rating of the run's system.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RatingChange {
+    pub agent: String,
+    pub before: f64,
+    pub after: f64,
+}
+
+/// Everything that differs between `before` and `after` for the agents of
+/// `record`. Tier moves are passed in since they are decided, not derived.
+pub fn diff(
+    before: &RunState,
+    after: &RunState,
+    old_commitments: &BTreeMap<String, Commitment>,
+    record: &RunRecord,
+    tiers: Vec<TierMove>,
+) -> StateDiff {
+    let mut out = StateDiff { tiers, ..StateDiff::default() };
+
+    for r in &record.results {
+        let (b, a) = (before.ledger.balance(&r.agent), after.ledger.balance(&r.agent));
+        if b != a {
+            out.ledger.push(BalanceChange { agent: r.agent.clone(), before: b, after: a });
+        }
+
+        let bound = before.wallets.wallet_of(&r.agent);
+        if let Some(w) = after.wallets.wallet_of(&r.agent) {
+            if bound != Some(w) {
+                out.bindings.push(BindingChange {
+                    agent: r.agent.clone(),
+                    before: bound.map(String::from),
+                    after: w.to_string(),
+                });
+            }
+        }
+
+        let rating = |s: &RunState| {
+            s.ratings
+                .system(&record.system)
+                .and_then(|sys| sys.agents.get(&r.agent))
+                .map(|x| x.rating)
+        };
+        if let Some(a) = rating(after) {
+            let b = rating(before).unwrap_or_default();
+            if b != a {
+                out.ratings.push(RatingChange { agent: r.agent.clone(), before: b, after: a });
+            }
+        }
+    }
+
+    let mut wallets: Vec<(&String, &u64)> = after.wallets.all_wallets().iter().collect();
+    wallets.sort();
+    for (w, &a) in wallets {
+        let b = before.wallets.balance(w);
+        if b != a {
+            out.wallets.push(WalletChange { wallet: w.clone(), before: b, after: a });
+        }
+    }
+
+    for c in &record.commitments {
+        let old = old_commitments.get(&c.agent).map(|o| o.merkle_root.clone());
+        if old.as_deref() != Some(c.merkle_root.as_str()) {
+            out.commitments.push(CommitmentChange {
+                agent: c.agent.clone(),
+                before: old,
+                after: c.merkle_root.clone(),
+            });
+        }
+    }
+
+    out
+}
+
+pub fn print_diff(diff: &StateDiff) {
+    for c in &diff.ledger {
+        println!("  ledger   {} {} -> {}", c.agent, c.before, c.after);
+    }
+    for c in &diff.wallets {
+        println!("  wallet   {} {} -> {}", c.wallet, c.before, c.after);
+    }
+    for c in &diff.bindings {
+        println!(
+            "  binding  {} {} -> {}",
+            c.agent,
+            c.before.as_deref().unwrap_or("-"),
+            c.after
+        );
+    }
+    for c in &diff.commitments {
+        println!(
+            "  commit   {} {} -> {}",
+            c.agent,
+            c.before.as_deref().unwrap_or("-"),
+            c.after
+        );
+    }
+    for m in &diff.tiers {
+        println!("  tier     {} {} -> {}", m.agent, m.from, m.to);
+    }
+    for c in &diff.ratings {
+        println!("  rating   {} {:.1} -> {:.1}", c.agent, c.before, c.after);
+    }
+}
+
+/* ------------------------------
+   Plans (nets run --plan-out / nets apply)
+-------------------------------*/
+
+/// A dry run's outcome, ready to commit later.
+#[derive(Serialize, Deserialize)]
+pub struct Plan {
+    /// state.json when the plan was made; absent if there was none.
+    pub base: Option<String>,
+    /// state.json to write on apply, byte for byte.
+    pub state: String,
+    pub diff: StateDiff,
+    pub record: RunRecord,
+}
+
+impl Plan {
+    pub fn save(&self, path: &str) {
+        fs::write(path, serde_json::to_string_pretty(self).unwrap()).unwrap_or_else(|_| {
+            eprintln!("cannot write plan: {}", path);
+            std::process::exit(1);
+        });
+    }
+}
+
+/// `nets apply` JSON output.
+#[derive(Serialize)]
+pub struct ApplyReport {
+    pub run_id: String,
+    pub diff: StateDiff,
+}
+
+pub fn apply(cfg: &Config, plan_path: String, format: OutputFormat) {
+    let data = fs::read_to_string(&plan_path).unwrap_or_else(|_| {
+        eprintln!("plan file not found: {}", plan_path);
+        std::process::exit(1);
+    });
+    let plan: Plan = serde_json::from_str(&data).unwrap_or_else(|_| {
+        eprintln!("invalid plan format");
+        std::process::exit(1);
+    });
+
+    if fs::read_to_string(&cfg.state_path).ok() != plan.base {
+        eprintln!(
+            "state changed since the plan was made: {}; re-run `nets run --plan-out`",
+            cfg.state_path
+        );
+        std::process::exit(1);
+    }
+
+    state_file::replace(&cfg.state_path, &plan.state);
+
+    let mut record = plan.record;
+    record.id = run_record::next_id(&cfg.history_dir);
+    record.committed = true;
+    run_record::save(&cfg.history_dir, &record);
+
+    if format.is_text() {
+        println!("applied {}:", plan_path);
+        print_diff(&plan.diff);
+        println!("\nrun recorded as {}", record.id);
+    }
+    format.emit("apply", &ApplyReport { run_id: record.id, diff: plan.diff });
+}
@@ -0,0 +1,49 @@
+use std::cmp::Ordering;
+
+use nets::{ledger::Ledger, league_state::LeagueState, persist};
+
+use crate::cli::PlayArgs;
+use crate::commands::{head_to_head, run::resolve_params};
+use crate::config::Config;
+use crate::systems::{self, Session};
+
+/* ------------------------------
+   nets play
+-------------------------------*/
+
+/// Play a seeded instance at the terminal, then watch the agent play the
+/// same one; the higher score wins. Never writes to state.
+pub fn play(cfg: &Config, args: PlayArgs) {
+    let plugin = systems::resolve(&cfg.system(args.system));
+    let params = resolve_params(cfg, plugin, args.params, args.params_file);
+    let league = args.league.unwrap_or(cfg.league).league();
+    let limits = args.limits.apply(cfg.limits);
+
+    let (id, wasm) = head_to_head::load(&args.agent);
+
+    // Scoring may depend on capacity, so read the ledger, but never save it.
+    let mut ledger = Ledger::new();
+    let mut league_state = LeagueState::default();
+    persist::load(&cfg.state_path, &mut ledger, &mut league_state);
+
+    let session = Session { seed: args.seed, human: !args.watch, pause: !args.auto };
+    let scores = plugin
+        .play_interactive(&params, (&id, &wasm), &ledger, &league, &limits, &session)
+        .unwrap_or_else(|e| {
+            eprintln!("cannot play {}: {}", id, e);
+            std::process::exit(1);
+        });
+
+    println!();
+    let Some(human) = scores.human else {
+        println!("{} score={}", id, scores.agent);
+        return;
+    };
+
+    let result = match human.cmp(&scores.agent) {
+        Ordering::Greater => "you win",
+        Ordering::Less => "agent wins",
+        Ordering::Equal => "draw",
+    };
+    println!("you score={} {} score={}: {}", human, id, scores.agent, result);
+}
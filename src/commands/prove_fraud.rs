@@ -1,23 +1,43 @@
 use std::fs;
 
-use nets::{
-    fraud::TraceStepProof,
-    match_trace::run_match_with_trace,
-    snake::SnakeSystem,
-};
+use serde::Serialize;
+
+use nets::fraud::TraceStepProof;
 
 use crate::commands::{
-    commitment::{Commitment, FraudProofEnvelope},
-    snake_agent::SnakeWasmAgent,
-    fraud_helpers::build_first_divergent_proof,
-    slash,
+    commitment::{self, Commitment, FraudProofEnvelope},
+    fraud_helpers::{self, build_first_divergent_proof},
+    output::OutputFormat,
+    slash::{self, SlashReport},
 };
+use crate::config::Config;
+
+/// `nets prove-fraud` JSON output. `proof` is omitted when it was written
+/// to `--out`.
+#[derive(Serialize)]
+pub struct ProveFraudReport {
+    pub agent: String,
+    pub system: String,
+    pub committed_root: String,
+    pub recomputed_root: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_index: Option<usize>,
+    pub step_index: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof: Option<FraudProofEnvelope>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slashed: Option<SlashReport>,
+}
 
 pub fn prove_fraud(
+    cfg: &Config,
     commitment_path: String,
     agent_wasm: String,
     out: Option<String>,
     do_slash: bool,
+    format: OutputFormat,
 ) {
     let data = fs::read_to_string(&commitment_path)
         .unwrap_or_else(|_| {
@@ -36,28 +56,48 @@ pub fn prove_fraud(
         std::process::exit(1);
     });
 
-    let mut agent = SnakeWasmAgent::load(commitment.agent.clone(), &wasm);
-
-    let system = SnakeSystem::new(
-        commitment.system_params.width,
-        commitment.system_params.height,
-        commitment.system_params.steps,
-    );
-
-    let recomputed = run_match_with_trace(system.clone(), &mut agent);
-
-    let committed_root: [u8; 32] = hex::decode(&commitment.merkle_root)
-        .unwrap()
-        .try_into()
-        .unwrap();
+    let opponent = commitment::workspace_wasm(cfg);
+    let replay = commitment::replay(&commitment, &wasm, opponent).unwrap_or_else(|e| {
+        eprintln!("cannot replay agent {}: {}", commitment.agent, e);
+        std::process::exit(1);
+    });
 
-    if recomputed.merkle.root() == committed_root {
+    // The proof is over the first committed match that replays differently.
+    let Some(index) = replay.mismatch else {
         eprintln!("no fraud detected: recomputed root matches commitment");
         std::process::exit(1);
+    };
+    let recomputed = &replay.traces[index];
+    let committed_match = &commitment.committed_matches()[index];
+
+    // The proof points at a step of the committed trace, so it needs the
+    // step hashes the agent committed to, not just their root. They are
+    // rebuilt from the match's trace file.
+    let steps = commitment
+        .match_file(&cfg.traces_dir, index)
+        .expect("replayed match is committed")
+        .unwrap_or_else(|e| {
+            eprintln!("cannot read the committed trace: {}", e);
+            std::process::exit(1);
+        })
+        .steps;
+    if steps.is_empty() {
+        eprintln!(
+            "fraud detected but the commitment has no trace file to prove it with; \
+             it predates trace files"
+        );
+        std::process::exit(1);
+    }
+    let committed = fraud_helpers::trace_from_steps(&steps).unwrap_or_else(|e| {
+        eprintln!("invalid commitment: {}", e);
+        std::process::exit(1);
+    });
+    if hex::encode(committed.merkle.root()) != committed_match.merkle_root {
+        eprintln!("invalid commitment: recorded trace steps do not hash to the committed root");
+        std::process::exit(1);
     }
 
-    // Build minimal fraud proof
-    let proof: TraceStepProof = match build_first_divergent_proof(&recomputed, &recomputed) {
+    let proof: TraceStepProof = match build_first_divergent_proof(&committed, recomputed) {
         Some(p) => p,
         None => {
             eprintln!("fraud detected but no divergent step found");
@@ -69,20 +109,45 @@ pub fn prove_fraud(
         agent: commitment.agent.clone(),
         system: commitment.system.clone(),
         committed_root: commitment.merkle_root.clone(),
+        match_index: (!commitment.matches.is_empty()).then_some(index),
+        match_root: (!commitment.matches.is_empty()).then(|| committed_match.merkle_root.clone()),
         proof,
     };
 
-    let json = serde_json::to_string_pretty(&envelope).unwrap();
+    let mut report = ProveFraudReport {
+        agent: commitment.agent.clone(),
+        system: commitment.system.clone(),
+        committed_root: commitment.merkle_root.clone(),
+        recomputed_root: hex::encode(replay.root),
+        match_index: envelope.match_index,
+        step_index: envelope.proof.step_index,
+        proof_path: None,
+        proof: None,
+        slashed: None,
+    };
 
     if let Some(path) = out {
+        let json = serde_json::to_string_pretty(&envelope).unwrap();
         fs::write(&path, &json).unwrap();
-        println!("fraud proof written to {}", path);
+        if format.is_text() {
+            println!("fraud proof written to {}", path);
+        }
+        report.proof_path = Some(path);
     } else {
-        println!("{}", json);
+        if format.is_text() {
+            println!("{}", serde_json::to_string_pretty(&envelope).unwrap());
+        }
+        report.proof = Some(envelope);
     }
 
     if do_slash {
-        println!("auto-slashing agent {}", commitment.agent);
-        slash::slash(commitment.agent.clone(), 50);
+        let slashed = slash::apply(cfg, commitment.agent.clone(), None);
+        if format.is_text() {
+            println!("auto-slashing agent {}", commitment.agent);
+            println!("slashed agent {} by {}", slashed.agent, slashed.amount);
+        }
+        report.slashed = Some(slashed);
     }
+
+    format.emit("prove_fraud", &report);
 }
@@ -0,0 +1,399 @@
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+
+use serde::{Serialize, Deserialize};
+
+use crate::commands::output::OutputFormat;
+use crate::commands::state_file;
+use crate::commands::tournament::Pairing;
+use crate::config::Config;
+use crate::systems::Standing;
+
+/* ------------------------------
+   Ratings
+-------------------------------*/
+
+const ELO_K: f64 = 32.0;
+
+/// Glicko-2 system constant; smaller values change volatility more slowly.
+const TAU: f64 = 0.5;
+/// Conversion between the Glicko and Glicko-2 scales.
+const SCALE: f64 = 173.7178;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Rating {
+    pub elo: f64,
+    /// Glicko-2 rating, deviation and volatility, on the Glicko scale.
+    pub rating: f64,
+    pub rd: f64,
+    pub volatility: f64,
+    pub games: u64,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self { elo: 1500.0, rating: 1500.0, rd: 350.0, volatility: 0.06, games: 0 }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RatingEntry {
+    pub period: u64,
+    pub agent: String,
+    pub elo: f64,
+    pub rating: f64,
+    pub rd: f64,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct SystemRatings {
+    /// One rating period per committed run.
+    pub periods: u64,
+    pub agents: BTreeMap<String, Rating>,
+    pub history: Vec<RatingEntry>,
+}
+
+const RATINGS_KEY: &str = "ratings";
+
+/// Ratings per system, kept next to the league state.
+#[derive(Default, Serialize, Deserialize)]
+pub struct RatingBook(BTreeMap<String, SystemRatings>);
+
+/// `(a, b, score for a, weight)` with 1.0 a win, 0.5 a draw and 0.0 a
+/// loss. `weight` scales the Elo K: 1.0 for a game actually played.
+pub type Outcome = (String, String, f64, f64);
+
+impl RatingBook {
+    pub fn load(path: &str) -> Self {
+        state_file::load_section(path, RATINGS_KEY)
+    }
+
+    pub fn save(&self, path: &str) {
+        state_file::save_section(path, RATINGS_KEY, self);
+    }
+
+    pub fn system(&self, system: &str) -> Option<&SystemRatings> {
+        self.0.get(system)
+    }
+
+    /// Apply one rating period for `system`. Rated agents that did not play
+    /// only have their Glicko deviation grow.
+    pub fn update(&mut self, system: &str, outcomes: &[Outcome]) {
+        let book = self.0.entry(system.to_string()).or_default();
+        book.periods += 1;
+
+        for (a, b, _, _) in outcomes {
+            book.agents.entry(a.clone()).or_default();
+            book.agents.entry(b.clone()).or_default();
+        }
+
+        // Every update in a period is computed from pre-period ratings.
+        let before = book.agents.clone();
+
+        for (id, rating) in book.agents.iter_mut() {
+            let played: Vec<(&Rating, f64, f64)> = outcomes
+                .iter()
+                .filter_map(|(a, b, s, w)| {
+                    if a == id {
+                        Some((&before[b], *s, *w))
+                    } else if b == id {
+                        Some((&before[a], 1.0 - s, *w))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            let games: Vec<(&Rating, f64)> = played.iter().map(|&(opp, s, _)| (opp, s)).collect();
+
+            let me = &before[id];
+            rating.elo = elo(me, &played);
+            let (r, rd, vol) = glicko2(me, &games);
+            rating.rating = r;
+            rating.rd = rd;
+            rating.volatility = vol;
+            rating.games += played.len() as u64;
+
+            if !played.is_empty() {
+                book.history.push(RatingEntry {
+                    period: book.periods,
+                    agent: id.clone(),
+                    elo: rating.elo,
+                    rating: rating.rating,
+                    rd: rating.rd,
+                });
+            }
+        }
+    }
+}
+
+/// Pairwise outcomes implied by a ranked list: every agent beats every
+/// agent with a lower score and draws with equal scores. Each agent's
+/// n-1 virtual games share one game's weight, so one run moves an Elo
+/// rating by at most K however many agents it had.
+pub fn outcomes_from_standings(standings: &[Standing]) -> Vec<Outcome> {
+    let weight = 1.0 / standings.len().saturating_sub(1).max(1) as f64;
+    let mut out = Vec::new();
+    for (i, a) in standings.iter().enumerate() {
+        for b in &standings[i + 1..] {
+            let s = match a.total_score.cmp(&b.total_score) {
+                std::cmp::Ordering::Greater => 1.0,
+                std::cmp::Ordering::Less => 0.0,
+                std::cmp::Ordering::Equal => 0.5,
+            };
+            out.push((a.agent_id.clone(), b.agent_id.clone(), s, weight));
+        }
+    }
+    out
+}
+
+/// Outcomes of played pairings; byes are not rated.
+pub fn outcomes_from_pairings(pairings: &[Pairing]) -> Vec<Outcome> {
+    pairings
+        .iter()
+        .filter_map(|p| {
+            let b = p.b.clone()?;
+            let s = match &p.winner {
+                Some(w) if *w == p.a => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            };
+            Some((p.a.clone(), b, s, 1.0))
+        })
+        .collect()
+}
+
+fn elo(me: &Rating, played: &[(&Rating, f64, f64)]) -> f64 {
+    let delta: f64 = played
+        .iter()
+        .map(|(opp, s, weight)| {
+            let expected = 1.0 / (1.0 + 10f64.powf((opp.elo - me.elo) / 400.0));
+            ELO_K * weight * (s - expected)
+        })
+        .sum();
+    me.elo + delta
+}
+
+/// One Glicko-2 rating period (Glickman, "Example of the Glicko-2 system").
+fn glicko2(me: &Rating, played: &[(&Rating, f64)]) -> (f64, f64, f64) {
+    let mu = (me.rating - 1500.0) / SCALE;
+    let phi = me.rd / SCALE;
+    let sigma = me.volatility;
+
+    if played.is_empty() {
+        let phi = (phi * phi + sigma * sigma).sqrt();
+        return (me.rating, (phi * SCALE).min(350.0), sigma);
+    }
+
+    let g = |phi_j: f64| 1.0 / (1.0 + 3.0 * phi_j * phi_j / (PI * PI)).sqrt();
+    let e = |mu_j: f64, phi_j: f64| 1.0 / (1.0 + (-g(phi_j) * (mu - mu_j)).exp());
+
+    let mut v_inv = 0.0;
+    let mut sum = 0.0;
+    for (opp, s) in played {
+        let mu_j = (opp.rating - 1500.0) / SCALE;
+        let phi_j = opp.rd / SCALE;
+        let e_j = e(mu_j, phi_j);
+        v_inv += g(phi_j).powi(2) * e_j * (1.0 - e_j);
+        sum += g(phi_j) * (s - e_j);
+    }
+    let v = 1.0 / v_inv;
+    let delta = v * sum;
+
+    // New volatility via the Illinois algorithm.
+    let a = (sigma * sigma).ln();
+    let f = |x: f64| {
+        let ex = x.exp();
+        ex * (delta * delta - phi * phi - v - ex) / (2.0 * (phi * phi + v + ex).powi(2))
+            - (x - a) / (TAU * TAU)
+    };
+
+    let mut lo = a;
+    let mut hi = if delta * delta > phi * phi + v {
+        (delta * delta - phi * phi - v).ln()
+    } else {
+        let mut k = 1.0;
+        while f(a - k * TAU) < 0.0 {
+            k += 1.0;
+        }
+        a - k * TAU
+    };
+
+    let (mut f_lo, mut f_hi) = (f(lo), f(hi));
+    while (hi - lo).abs() > 1e-6 {
+        let c = lo + (lo - hi) * f_lo / (f_hi - f_lo);
+        let f_c = f(c);
+        if f_c * f_hi <= 0.0 {
+            lo = hi;
+            f_lo = f_hi;
+        } else {
+            f_lo /= 2.0;
+        }
+        hi = c;
+        f_hi = f_c;
+    }
+    let sigma = (lo / 2.0).exp();
+
+    let phi_star = (phi * phi + sigma * sigma).sqrt();
+    let phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / v).sqrt();
+    let mu = mu + phi * phi * sum;
+
+    (mu * SCALE + 1500.0, phi * SCALE, sigma)
+}
+
+/* ------------------------------
+   nets ratings
+-------------------------------*/
+
+/// `nets ratings` JSON output: one agent's history with `--agent`, else
+/// the current ratings.
+#[derive(Serialize)]
+pub struct RatingsReport {
+    pub system: String,
+    pub periods: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agents: Option<Vec<RatingRow>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history: Option<Vec<RatingEntry>>,
+}
+
+#[derive(Serialize)]
+pub struct RatingRow {
+    pub agent: String,
+    #[serde(flatten)]
+    pub rating: Rating,
+}
+
+pub fn show(cfg: &Config, system: Option<String>, agent: Option<String>, format: OutputFormat) {
+    let system = cfg.system(system);
+    let book = RatingBook::load(&cfg.state_path);
+    if format.is_text() && book.system(&system).is_none() {
+        println!("no ratings for system {}", system);
+        return;
+    }
+    let empty = SystemRatings::default();
+    let ratings = book.system(&system).unwrap_or(&empty);
+
+    let mut report = RatingsReport {
+        system: system.clone(),
+        periods: ratings.periods,
+        agents: None,
+        history: None,
+    };
+
+    if let Some(agent) = agent {
+        let history: Vec<RatingEntry> =
+            ratings.history.iter().filter(|h| h.agent == agent).cloned().collect();
+        if format.is_text() {
+            println!("{} ratings for {}:", system, agent);
+            for h in &history {
+                println!(
+                    "period={} elo={:.1} rating={:.1} rd={:.1}",
+                    h.period, h.elo, h.rating, h.rd
+                );
+            }
+        }
+        report.history = Some(history);
+    } else {
+        let mut rows: Vec<RatingRow> = ratings
+            .agents
+            .iter()
+            .map(|(id, r)| RatingRow { agent: id.clone(), rating: r.clone() })
+            .collect();
+        rows.sort_by(|a, b| b.rating.rating.total_cmp(&a.rating.rating));
+
+        if format.is_text() {
+            println!("{} ratings after {} periods:", system, ratings.periods);
+            for row in &rows {
+                let r = &row.rating;
+                println!(
+                    "{} elo={:.1} rating={:.1} rd={:.1} volatility={:.4} games={}",
+                    row.agent, r.elo, r.rating, r.rd, r.volatility, r.games
+                );
+            }
+        }
+        report.agents = Some(rows);
+    }
+
+    format.emit("ratings", &report);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rated(rating: f64, rd: f64) -> Rating {
+        Rating { rating, rd, ..Rating::default() }
+    }
+
+    fn close(got: f64, want: f64, tolerance: f64) {
+        assert!((got - want).abs() < tolerance, "got {}, want {}", got, want);
+    }
+
+    #[test]
+    fn glicko2_matches_glickmans_worked_example() {
+        let me = rated(1500.0, 200.0);
+        let (o1, o2, o3) = (rated(1400.0, 30.0), rated(1550.0, 100.0), rated(1700.0, 300.0));
+
+        let (r, rd, vol) = glicko2(&me, &[(&o1, 1.0), (&o2, 0.0), (&o3, 0.0)]);
+        close(r, 1464.06, 0.01);
+        close(rd, 151.52, 0.01);
+        close(vol, 0.05999, 0.00001);
+    }
+
+    #[test]
+    fn glicko2_without_games_only_grows_the_deviation() {
+        let me = rated(1500.0, 200.0);
+        let (r, rd, vol) = glicko2(&me, &[]);
+        assert_eq!((r, vol), (1500.0, 0.06));
+        close(rd, 200.27, 0.01);
+        assert_eq!(glicko2(&rated(1500.0, 350.0), &[]).1, 350.0);
+    }
+
+    #[test]
+    fn elo_moves_by_half_k_between_equals() {
+        let (me, opp) = (Rating::default(), Rating::default());
+        close(elo(&me, &[(&opp, 1.0, 1.0)]), 1516.0, 1e-9);
+        close(elo(&me, &[(&opp, 0.5, 1.0)]), 1500.0, 1e-9);
+    }
+
+    #[test]
+    fn a_period_is_computed_from_pre_period_ratings() {
+        let mut book = RatingBook::default();
+        let outcomes = vec![("a".to_string(), "b".to_string(), 1.0, 1.0)];
+        book.update("rps", &outcomes);
+        book.update("rps", &[]);
+
+        let ratings = book.system("rps").unwrap();
+        assert_eq!(ratings.periods, 2);
+        close(ratings.agents["a"].elo, 1516.0, 1e-9);
+        close(ratings.agents["b"].elo, 1484.0, 1e-9);
+        assert_eq!(ratings.agents["a"].games, 1);
+        assert_eq!(ratings.history.len(), 2, "idle periods add no history");
+    }
+
+    #[test]
+    fn standings_imply_pairwise_outcomes() {
+        let standings: Vec<Standing> = [("a", 5), ("b", 5), ("c", 1)]
+            .iter()
+            .map(|&(id, s)| Standing { agent_id: id.into(), total_score: s, matches: 1 })
+            .collect();
+        let scores: Vec<f64> = outcomes_from_standings(&standings).iter().map(|o| o.2).collect();
+        assert_eq!(scores, vec![0.5, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn one_large_run_moves_elo_by_at_most_k() {
+        let standings: Vec<Standing> = (0..40)
+            .map(|i| Standing { agent_id: format!("a{:02}", i), total_score: 40 - i, matches: 1 })
+            .collect();
+        let mut book = RatingBook::default();
+        book.update("rps", &outcomes_from_standings(&standings));
+
+        let ratings = book.system("rps").unwrap();
+        for r in ratings.agents.values() {
+            assert!((r.elo - 1500.0).abs() <= ELO_K / 2.0 + 1e-9, "elo moved to {}", r.elo);
+        }
+        close(ratings.agents["a00"].elo, 1500.0 + ELO_K / 2.0, 1e-9);
+        close(ratings.agents["a39"].elo, 1500.0 - ELO_K / 2.0, 1e-9);
+    }
+}
@@ -0,0 +1,283 @@
+use std::fs;
+use std::thread;
+use std::time::Duration;
+
+use nets::match_trace::MatchTrace;
+
+use crate::cli::ReplayArgs;
+use crate::commands::{
+    commitment,
+    run::resolve_params,
+    trace_file::{TraceFile, TraceFileStep},
+};
+use crate::config::Config;
+use crate::systems::{self, console::{prompt, Frame}, guest::{Limits, OBS_ENCODING}, SystemPlugin};
+
+/* ------------------------------
+   nets replay
+-------------------------------*/
+
+/// Re-simulate one match and step through it: a trace file, a match of the
+/// agent's committed run, or a new match on `--seed`. Steps whose hashes
+/// differ from the file or commitment are marked.
+pub fn replay(cfg: &Config, args: ReplayArgs) {
+    let Some(target) = target(cfg, &args) else {
+        return;
+    };
+    let agent = &target.agent;
+
+    let wasm_path = args
+        .agent_wasm
+        .clone()
+        .unwrap_or_else(|| cfg.agent_wasm(agent).to_string_lossy().to_string());
+    let wasm = fs::read(&wasm_path).unwrap_or_else(|_| {
+        eprintln!("agent wasm not found: {}", wasm_path);
+        std::process::exit(1);
+    });
+
+    // Shared boards replay the whole table the match was recorded with.
+    let (seats, seat) =
+        commitment::seat_table(agent, &target.lineup, &wasm, commitment::workspace_wasm(cfg))
+            .unwrap_or_else(|e| {
+                eprintln!("cannot replay agent {}: {}", agent, e);
+                std::process::exit(1);
+            });
+    let table: Vec<(&str, &[u8])> =
+        seats.iter().map(|(id, w)| (id.as_str(), w.as_slice())).collect();
+
+    let (frames, trace) = target
+        .plugin
+        .record(&target.params, target.seed, &table, seat, &target.limits, target.encoding)
+        .unwrap_or_else(|e| {
+            eprintln!("cannot replay agent {}: {}", agent, e);
+            std::process::exit(1);
+        });
+    if frames.is_empty() {
+        println!("{} made no moves", agent);
+        return;
+    }
+
+    let differs: Vec<bool> = (0..frames.len())
+        .map(|i| match &target.expected {
+            Some(e) if !e.steps.is_empty() => step_differs(&e.steps, &trace, i),
+            _ => false,
+        })
+        .collect();
+
+    let step = |i: usize| trace.steps.get(i).map(|s| s.step).unwrap_or(i as u64);
+    let status = target.expected.as_ref().map(|e| {
+        let root_matches = hex::encode(trace.merkle.root()) == e.root;
+        match differs.iter().position(|&d| d) {
+            None if root_matches => format!("replay matches {}", e.source),
+            None if e.steps.is_empty() => format!("replay does not match the root of {}", e.source),
+            None => format!("replay matches every step but not the root of {}", e.source),
+            Some(i) => format!("replay differs from {} from step {}", e.source, step(i)),
+        }
+    });
+
+    let viewer = Viewer {
+        title: format!("{} {} seed={}", target.plugin.name(), agent, target.seed),
+        status,
+        source: target.expected.as_ref().map(|e| e.source).unwrap_or(""),
+        frames: &frames,
+        trace: &trace,
+        differs: &differs,
+    };
+    viewer.run(args.delay);
+}
+
+/// One match to replay, with what it was recorded as when known.
+struct Target {
+    plugin: &'static dyn SystemPlugin,
+    params: serde_json::Value,
+    seed: u64,
+    limits: Limits,
+    /// Observation encoding the match was played with.
+    encoding: u32,
+    agent: String,
+    /// Every agent at the table in seat order; empty when it played alone.
+    lineup: Vec<String>,
+    expected: Option<Expected>,
+}
+
+/// Steps and root a replay should reproduce, and where they come from.
+struct Expected {
+    source: &'static str,
+    /// Empty for commitments made before steps were recorded.
+    steps: Vec<TraceFileStep>,
+    root: String,
+}
+
+/// The match `args` selects. `None` once the agent's committed matches
+/// have been listed because none was picked.
+fn target(cfg: &Config, args: &ReplayArgs) -> Option<Target> {
+    if let Some(path) = &args.trace {
+        let data = fs::read_to_string(path).unwrap_or_else(|_| {
+            eprintln!("trace file not found: {}", path);
+            std::process::exit(1);
+        });
+        let f: TraceFile = serde_json::from_str(&data).unwrap_or_else(|_| {
+            eprintln!("invalid trace format");
+            std::process::exit(1);
+        });
+        return Some(Target {
+            plugin: systems::resolve(&f.system),
+            params: f.system_params,
+            seed: f.seed,
+            limits: f.limits,
+            encoding: f.encoding,
+            agent: f.agent,
+            lineup: f.lineup,
+            expected: Some(Expected {
+                source: "the trace file",
+                steps: f.steps,
+                root: f.merkle_root,
+            }),
+        });
+    }
+
+    let agent = args.agent.clone().unwrap_or_default();
+    if let Some(seed) = args.seed {
+        let plugin = systems::resolve(&cfg.system(args.system.clone()));
+        return Some(Target {
+            plugin,
+            params: resolve_params(cfg, plugin, args.params.clone(), args.params_file.clone()),
+            seed,
+            limits: args.limits.apply(cfg.limits),
+            encoding: OBS_ENCODING,
+            agent,
+            lineup: Vec::new(),
+            expected: None,
+        });
+    }
+
+    // `nets run --seed` is a run seed; each match has its own seed derived
+    // from it, so committed matches are picked by index.
+    let record = commitment::load_records(&cfg.state_path)
+        .remove(&agent)
+        .unwrap_or_else(|| {
+            eprintln!(
+                "no committed run recorded for agent {}; pass --seed to play a new match",
+                agent
+            );
+            std::process::exit(1);
+        });
+    let matches = record.committed_matches();
+
+    let Some(index) = args.match_index else {
+        println!(
+            "{} committed to {} {} match(es), run seed {}:",
+            agent,
+            matches.len(),
+            record.system,
+            record.seed
+        );
+        for (i, m) in matches.iter().enumerate() {
+            let table = if m.lineup.is_empty() { String::new() } else { m.lineup.join(", ") };
+            println!("  match {} seed={} merkle_root={} {}", i, m.seed, m.merkle_root, table);
+        }
+        println!("replay one with `nets replay --agent {} --match <index>`", agent);
+        return None;
+    };
+
+    let f = record.match_file(&cfg.traces_dir, index).unwrap_or_else(|| {
+        eprintln!(
+            "agent {} committed to {} match(es); there is no match {}",
+            agent,
+            matches.len(),
+            index
+        );
+        std::process::exit(1);
+    });
+    let f = f.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+    Some(Target {
+        plugin: systems::resolve(&f.system),
+        params: f.system_params,
+        seed: f.seed,
+        limits: f.limits,
+        encoding: f.encoding,
+        agent,
+        lineup: f.lineup,
+        expected: Some(Expected { source: "the commitment", steps: f.steps, root: f.merkle_root }),
+    })
+}
+
+/// Whether step `i` of `trace` hashes differently from the recorded steps.
+fn step_differs(steps: &[TraceFileStep], trace: &MatchTrace, i: usize) -> bool {
+    match (steps.get(i), trace.steps.get(i)) {
+        (Some(want), Some(got)) => {
+            want.obs_hash != hex::encode(got.obs_hash)
+                || want.action_hash != hex::encode(got.action_hash)
+        }
+        _ => true,
+    }
+}
+
+const KEYS: &str = "enter/n: next, b: back, g <step>: seek, d: next difference, p: play, q: quit";
+
+struct Viewer<'a> {
+    title: String,
+    /// How the replay compares with the trace file or commitment, if any.
+    status: Option<String>,
+    /// What `status` compares with, e.g. `the trace file`.
+    source: &'static str,
+    frames: &'a [Frame],
+    trace: &'a MatchTrace,
+    differs: &'a [bool],
+}
+
+impl Viewer<'_> {
+    /// `TraceStep.step` of frame `i`.
+    fn step(&self, i: usize) -> u64 {
+        self.trace.steps.get(i).map(|s| s.step).unwrap_or(i as u64)
+    }
+
+    fn show(&self, i: usize) {
+        print!("\x1b[2J\x1b[H");
+        println!("{}  step {} / {}", self.title, self.step(i), self.step(self.frames.len() - 1));
+        if let Some(status) = &self.status {
+            println!("{}", status);
+        }
+        println!("{}", self.frames[i].board);
+        println!("plays {}", self.frames[i].action);
+        if self.differs[i] {
+            println!("differs from {} at this step", self.source);
+        }
+    }
+
+    fn run(&self, delay: u64) {
+        let last = self.frames.len() - 1;
+
+        let mut i = 0;
+        loop {
+            self.show(i);
+            let input = prompt(KEYS);
+            let mut words = input.split_whitespace();
+
+            match (words.next(), words.next()) {
+                (None, _) | (Some("n"), _) => i = (i + 1).min(last),
+                (Some("b"), _) => i = i.saturating_sub(1),
+                (Some("g"), Some(step)) => match step.parse::<u64>() {
+                    Ok(step) => i = (0..=last).rev().find(|&j| self.step(j) <= step).unwrap_or(0),
+                    Err(_) => continue,
+                },
+                (Some("d"), _) => {
+                    if let Some(j) = (i + 1..=last).find(|&j| self.differs[j]) {
+                        i = j;
+                    }
+                }
+                (Some("p"), _) => {
+                    while i < last {
+                        i += 1;
+                        self.show(i);
+                        thread::sleep(Duration::from_millis(delay));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
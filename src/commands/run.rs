@@ -1,245 +1,538 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
+use serde::Serialize;
+use serde_json::json;
+
 use nets::{
-    agent::Agent,
-    match_trace::run_match_with_trace,
-    league::League,
-    league_runner::{run_league, LeagueConfig},
     ledger::Ledger,
     evolution::evolve,
     persist,
     league_state::LeagueState,
-
-    snake::SnakeSystem,
-    chess::ChessSystem,
-    rps::RpsSystem,
 };
 
-use crate::commands::snake_agent::SnakeWasmAgent;
-use crate::commands::chess_agent::ChessWasmAgent;
-use crate::commands::rps_agent::RpsWasmAgent;
+use crate::cli::RunArgs;
+use crate::commands::{
+    commitment::{self, CommittedMatch, Commitment},
+    discovery::{self, Selection},
+    league::TierMap,
+    output::OutputFormat,
+    plan::{self, Plan, StateDiff, TierMove},
+    ratings::{self, RatingBook},
+    run_record::{self, AgentResult, RunRecord},
+    state_file,
+    tournament,
+    trace_file::{self, TraceFile},
+};
+use crate::config::Config;
+use crate::systems::{
+    self,
+    guest::{Module, OBS_ENCODING},
+    Disqualified, LeagueOutcome, LeagueSpec, Standing,
+};
 
 use crate::wallet::mock::MockWalletAdapter;
 use crate::wallet::adapter::WalletAdapter;
 
-pub fn run(system: String, matches: usize, commit: bool, wallet: Option<String>) {
-    let agents_dir = Path::new("agents");
-    if !agents_dir.exists() {
-        eprintln!("agents/ directory not found");
+/// `nets run` JSON output. With `--format ndjson` the same data streams
+/// as `skipped`, `pairing`, `standing`, `commitment`, `settlement` and
+/// `tier_move` events before the final `run` event.
+#[derive(Serialize)]
+pub struct RunReport {
+    /// The run record, as saved to the history directory.
+    pub run: RunRecord,
+    pub skipped: Vec<Skipped>,
+    pub settlement: Vec<Settlement>,
+    /// State changes; applied only with `--commit`.
+    pub diff: StateDiff,
+    /// Whether the record was saved to the history directory.
+    pub recorded: bool,
+}
+
+#[derive(Serialize)]
+pub struct Skipped {
+    pub agent: String,
+    pub reason: String,
+}
+
+#[derive(Serialize)]
+pub struct Settlement {
+    pub agent: String,
+    pub rank: usize,
+    pub reward: u64,
+    pub capacity_before: u64,
+}
+
+pub fn run(cfg: &Config, args: RunArgs, format: OutputFormat) {
+    let plugin = systems::resolve(&cfg.system(args.system));
+    let params = resolve_params(cfg, plugin, args.params, args.params_file);
+    if plugin.seats(&params) > 1 && args.tournament != tournament::Format::League {
+        eprintln!(
+            "{} seats several agents per match and runs only as a league",
+            plugin.name()
+        );
         std::process::exit(1);
     }
+    let commit = args.commit;
+    let started_at = run_record::now();
+    let payout = args.payout.unwrap_or_else(|| cfg.payout.clone());
 
-    let mut ledger = Ledger::new();
-    let mut league_state = LeagueState::default();
-    persist::load("state.json", &mut ledger, &mut league_state);
-
-    let mut wallet_adapter = MockWalletAdapter::load("state.json");
-    let run_wallet = wallet.unwrap_or_else(|| "default".into());
+    let agents_dir = Path::new(&cfg.agents_dir);
+    if !agents_dir.exists() {
+        eprintln!("agents directory not found: {}", cfg.agents_dir);
+        std::process::exit(1);
+    }
 
-    let league = League::bronze();
-    let league_cfg = LeagueConfig { matches_per_agent: matches };
+    let mut state = RunState::load(&cfg.state_path);
 
-    match system.as_str() {
-        /* =======================
-           SNAKE
-        ======================= */
-        "snake" => {
-            let mut agents = Vec::new();
+    let tier = args.league.unwrap_or(cfg.league);
+    let league = tier.league();
 
-            for entry in fs::read_dir(agents_dir).unwrap() {
-                let path = entry.unwrap().path();
-                if path.extension().and_then(|s| s.to_str()) != Some("wasm") {
-                    continue;
-                }
+    let selection = Selection {
+        include: args.agents,
+        exclude: cfg.exclude.iter().chain(&args.exclude).cloned().collect(),
+        tags: args.tags,
+    };
 
-                let id = path.file_stem().unwrap().to_string_lossy().to_string();
-                let wasm = fs::read(&path).unwrap();
+    let discovered = discovery::discover(&cfg.agents_dir);
+    let missing = selection.missing(&discovered);
+    if !missing.is_empty() {
+        eprintln!("agents not found in {}: {}", cfg.agents_dir, missing.join(", "));
+        std::process::exit(1);
+    }
 
-                wallet_adapter.bind_agent(&id, &run_wallet);
-                agents.push(SnakeWasmAgent::load(id, &wasm));
-            }
+    let mut agents = Vec::new();
+    let mut skipped = Vec::new();
+    let mut disqualified = Vec::new();
+    let mut wallets = BTreeMap::new();
 
-            let system = SnakeSystem::new(10, 10, 300);
-            let results = run_league(system.clone(), &mut agents, &ledger, &league, &league_cfg);
-
-            println!("\nresults:");
-            for r in &results {
-                println!(
-                    "{} total_score={} matches={}",
-                    r.agent_id,
-                    r.total_score,
-                    r.matches.len()
-                );
+    for agent in discovered {
+        if !selection.matches(&agent) {
+            continue;
+        }
+        let agent_tier = state.tiers.tier(&agent.id);
+        let reason = if agent_tier != tier {
+            Some(format!("in tier {}, run is {}", agent_tier, tier))
+        } else if let Some(e) = &agent.manifest_error {
+            Some(e.clone())
+        } else if !agent.manifest.supports(plugin.name()) {
+            Some(format!("does not support {}", plugin.name()))
+        } else if !agent.manifest.abi_supported() {
+            Some(format!(
+                "abi_version {} (expected {} to {})",
+                agent.manifest.abi_version.unwrap_or_default(),
+                discovery::MIN_AGENT_ABI_VERSION,
+                discovery::AGENT_ABI_VERSION
+            ))
+        } else if agent.manifest.abi() < plugin.min_abi() {
+            Some(format!(
+                "abi_version {} ({} agents need guest ABI {} or later)",
+                agent.manifest.abi(),
+                plugin.name(),
+                plugin.min_abi()
+            ))
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            if format.is_text() {
+                println!("skipping {}: {}", agent.id, reason);
             }
+            let s = Skipped { agent: agent.id, reason };
+            format.event("skipped", &s);
+            skipped.push(s);
+            continue;
+        }
 
-            let mut commitments = Vec::new();
-            println!("\ncommitments:");
-            for agent in agents.iter_mut() {
-                let trace = run_match_with_trace(system.clone(), agent);
-                let root = trace.merkle.root();
-                println!("{} merkle_root={:x?}", agent.id(), root);
-                commitments.push((agent.id(), root));
+        // Compiled once here; every match instantiates the same module.
+        let module = fs::read(&agent.wasm_path)
+            .map_err(|e| format!("cannot read wasm: {}", e))
+            .and_then(|wasm| Module::compile(&wasm));
+        let module = match module {
+            Ok(module) => module,
+            Err(reason) => {
+                disqualified.push(Disqualified { agent: agent.id, reason });
+                continue;
             }
+        };
 
-            settle_and_persist(
-                results,
-                &mut ledger,
-                &mut wallet_adapter,
-                &mut league_state,
-                commitments,
-                commit,
-            );
-        }
+        // `--wallet` overrides the manifest owner for every agent in the run.
+        let wallet = args
+            .wallet
+            .clone()
+            .or_else(|| agent.manifest.owner_wallet.clone())
+            .unwrap_or_else(|| cfg.default_wallet.clone());
+        wallets.insert(agent.id.clone(), wallet);
+        agents.push((agent.id, module));
+    }
 
-        /* =======================
-           CHESS
-        ======================= */
-        "chess" => {
-            let mut agents = Vec::new();
+    if agents.is_empty() && disqualified.is_empty() {
+        eprintln!("no selected agents in the {} league", tier);
+        std::process::exit(1);
+    }
 
-            for entry in fs::read_dir(agents_dir).unwrap() {
-                let path = entry.unwrap().path();
-                if path.extension().and_then(|s| s.to_str()) != Some("wasm") {
-                    continue;
-                }
+    let spec = LeagueSpec {
+        params: &params,
+        league: &league,
+        matches: args.matches,
+        seed: args.seed,
+        jobs: args.jobs,
+        limits: args.limits.apply(cfg.limits),
+    };
+    let played = tournament::play(&args.tournament, plugin, &spec, &agents, &state.ledger);
+    let mut outcome = played.league;
+    disqualified.append(&mut outcome.disqualified);
+
+    // Only agents that finished are bound and paid.
+    for s in &outcome.standings {
+        state.wallets.bind_agent(&s.agent_id, &wallets[&s.agent_id]);
+    }
 
-                let id = path.file_stem().unwrap().to_string_lossy().to_string();
-                let wasm = fs::read(&path).unwrap();
+    for d in &disqualified {
+        format.event("disqualified", d);
+    }
 
-                wallet_adapter.bind_agent(&id, &run_wallet);
-                agents.push(ChessWasmAgent::load(id, &wasm));
-            }
+    for p in &played.pairings {
+        format.event("pairing", p);
+    }
+    for r in &outcome.standings {
+        format.event(
+            "standing",
+            &json!({ "agent": r.agent_id, "total_score": r.total_score, "matches": r.matches }),
+        );
+    }
 
-            let system = ChessSystem::new(200);
-            let results = run_league(system.clone(), &mut agents, &ledger, &league, &league_cfg);
-
-            println!("\nresults:");
-            for r in &results {
-                println!(
-                    "{} total_score={} matches={}",
-                    r.agent_id,
-                    r.total_score,
-                    r.matches.len()
-                );
-            }
+    if format.is_text() {
+        for d in &disqualified {
+            println!("disqualified {}: {}", d.agent, d.reason);
+        }
+        print_outcome(&args.tournament, &played.pairings, &outcome);
+    }
 
-            let mut commitments = Vec::new();
-            println!("\ncommitments:");
-            for agent in agents.iter_mut() {
-                let trace = run_match_with_trace(system.clone(), agent);
-                let root = trace.merkle.root();
-                println!("{} merkle_root={:x?}", agent.id(), root);
-                commitments.push((agent.id(), root));
+    let (nets_core_version, cli_version) = run_record::versions();
+    let record = RunRecord {
+        id: run_record::next_id(&cfg.history_dir),
+        started_at,
+        committed: commit,
+        nets_core_version,
+        cli_version,
+        system: plugin.name().into(),
+        system_params: params.clone(),
+        seed: args.seed,
+        league: tier,
+        format: args.tournament.clone(),
+        payout,
+        results: Vec::new(),
+        pairings: played.pairings,
+        disqualified,
+        commitments: outcome
+            .commitments
+            .iter()
+            .map(|t| Commitment {
+                system: plugin.name().into(),
+                agent: t.agent.clone(),
+                merkle_root: hex::encode(t.root()),
+                system_params: params.clone(),
+                seed: args.seed,
+                limits: spec.limits,
+                encoding: OBS_ENCODING,
+                lineup: Vec::new(),
+                steps: Vec::new(),
+                matches: t
+                    .matches
+                    .iter()
+                    .map(|m| CommittedMatch {
+                        seed: m.seed,
+                        merkle_root: hex::encode(m.root),
+                        lineup: m.lineup.clone(),
+                        trace: Some(trace_file::committed_name(
+                            &t.agent,
+                            m.seed,
+                            &hex::encode(m.root),
+                        )),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    };
+
+    // Step hashes stay out of state, history and plans; whatever keeps a
+    // commitment also gets the trace file each of its matches points at.
+    if commit || args.record || args.plan_out.is_some() {
+        for (t, c) in outcome.commitments.iter().zip(&record.commitments) {
+            for (m, committed) in t.matches.iter().zip(&c.matches) {
+                let file = TraceFile {
+                    system: c.system.clone(),
+                    agent: c.agent.clone(),
+                    system_params: c.system_params.clone(),
+                    seed: m.seed,
+                    limits: c.limits,
+                    encoding: c.encoding,
+                    merkle_root: committed.merkle_root.clone(),
+                    lineup: m.lineup.clone(),
+                    steps: trace_file::step_records(&m.steps),
+                };
+                if let Some(name) = &committed.trace {
+                    trace_file::write_committed(&cfg.traces_dir, name, &file);
+                }
             }
-
-            settle_and_persist(
-                results,
-                &mut ledger,
-                &mut wallet_adapter,
-                &mut league_state,
-                commitments,
-                commit,
-            );
         }
+    }
+    for c in &record.commitments {
+        format.event("commitment", c);
+    }
 
-        /* =======================
-           ROCK PAPER SCISSORS
-        ======================= */
-        "rps" => {
-            let mut agents = Vec::new();
-
-            for entry in fs::read_dir(agents_dir).unwrap() {
-                let path = entry.unwrap().path();
-                if path.extension().and_then(|s| s.to_str()) != Some("wasm") {
-                    continue;
-                }
+    let mut report = RunReport {
+        run: record,
+        skipped,
+        settlement: Vec::new(),
+        diff: StateDiff::default(),
+        recorded: commit || args.record,
+    };
+
+    let staged = settle_and_persist(
+        cfg,
+        outcome.standings,
+        &mut report,
+        &mut state,
+        outcome.commitments.iter().map(|t| (t.agent.clone(), t.root())).collect(),
+        commit,
+        format,
+    );
+
+    if let Some(path) = &args.plan_out {
+        let plan = Plan {
+            base: state.base.clone(),
+            state: staged,
+            diff: report.diff.clone(),
+            record: report.run.clone(),
+        };
+        plan.save(path);
+        if format.is_text() {
+            println!("plan written to {}; commit it with `nets apply --plan {}`", path, path);
+        }
+    }
 
-                let id = path.file_stem().unwrap().to_string_lossy().to_string();
-                let wasm = fs::read(&path).unwrap();
+    if report.recorded {
+        run_record::save(&cfg.history_dir, &report.run);
+        if format.is_text() {
+            println!("\nrun recorded as {}", report.run.id);
+        }
+    }
 
-                wallet_adapter.bind_agent(&id, &run_wallet);
-                agents.push(RpsWasmAgent::load(id, &wasm));
-            }
+    format.emit("run", &report);
+}
 
-            let system = RpsSystem::new(100);
-            let results = run_league(system.clone(), &mut agents, &ledger, &league, &league_cfg);
-
-            println!("\nresults:");
-            for r in &results {
-                println!(
-                    "{} total_score={} matches={}",
-                    r.agent_id,
-                    r.total_score,
-                    r.matches.len()
-                );
+fn print_outcome(format: &tournament::Format, pairings: &[tournament::Pairing], outcome: &LeagueOutcome) {
+    if !pairings.is_empty() {
+        println!("\n{}:", format);
+        for p in pairings {
+            match &p.b {
+                Some(b) => println!(
+                    "round {} {}: {} {} vs {} {} -> {}",
+                    p.round,
+                    p.bracket,
+                    p.a,
+                    p.score_a,
+                    b,
+                    p.score_b,
+                    p.winner.as_deref().unwrap_or("draw")
+                ),
+                None => println!("round {} {}: {} bye", p.round, p.bracket, p.a),
             }
+        }
+    }
 
-            let mut commitments = Vec::new();
-            println!("\ncommitments:");
-            for agent in agents.iter_mut() {
-                let trace = run_match_with_trace(system.clone(), agent);
-                let root = trace.merkle.root();
-                println!("{} merkle_root={:x?}", agent.id(), root);
-                commitments.push((agent.id(), root));
-            }
+    println!("\nresults:");
+    for r in &outcome.standings {
+        println!(
+            "{} total_score={} matches={}",
+            r.agent_id,
+            r.total_score,
+            r.matches
+        );
+    }
 
-            settle_and_persist(
-                results,
-                &mut ledger,
-                &mut wallet_adapter,
-                &mut league_state,
-                commitments,
-                commit,
-            );
-        }
+    println!("\ncommitments:");
+    for t in &outcome.commitments {
+        println!("{} merkle_root={:x?} matches={}", t.agent, t.root(), t.matches.len());
+    }
+}
 
-        _ => {
-            eprintln!("unknown system '{}'", system);
+/// Config `[params.<system>]`, then the params file, then `--param`.
+pub fn resolve_params(
+    cfg: &Config,
+    plugin: &dyn systems::SystemPlugin,
+    overrides: Vec<String>,
+    params_file: Option<String>,
+) -> serde_json::Value {
+    let file: Option<serde_json::Value> = params_file.map(|path| {
+        let data = fs::read_to_string(&path).unwrap_or_else(|_| {
+            eprintln!("params file not found: {}", path);
+            std::process::exit(1);
+        });
+        serde_json::from_str(&data).unwrap_or_else(|_| {
+            eprintln!("invalid params file: {}", path);
             std::process::exit(1);
+        })
+    });
+
+    let overrides: Vec<(String, String)> = overrides
+        .iter()
+        .map(|kv| match kv.split_once('=') {
+            Some((k, v)) => (k.trim().to_string(), v.trim().to_string()),
+            None => {
+                eprintln!("invalid --param '{}', expected KEY=VALUE", kv);
+                std::process::exit(1);
+            }
+        })
+        .collect();
+
+    let mut base = cfg.system_params(plugin.name());
+    if let Some(serde_json::Value::Object(fields)) = file {
+        let merged = base.get_or_insert_with(|| serde_json::json!({}));
+        for (k, v) in fields {
+            merged[k] = v;
         }
+    } else if file.is_some() {
+        eprintln!("params file must contain a JSON object");
+        std::process::exit(1);
     }
+
+    plugin.resolve_params(base.as_ref(), &overrides).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    })
 }
 
 /* ------------------------------
    Settlement (non-generic)
 -------------------------------*/
 
-fn settle_and_persist<S: nets::system::System>(
-    mut results: Vec<nets::league_runner::LeagueResult<S>>,
-    ledger: &mut Ledger,
-    wallet_adapter: &mut MockWalletAdapter,
-    league_state: &mut LeagueState,
+/// Everything settlement reads and, on commit, writes back.
+pub struct RunState {
+    /// state.json as loaded; settlement stages its writes on top of it.
+    pub base: Option<String>,
+    pub ledger: Ledger,
+    pub league_state: LeagueState,
+    pub wallets: MockWalletAdapter,
+    pub tiers: TierMap,
+    pub ratings: RatingBook,
+}
+
+impl RunState {
+    pub fn load(path: &str) -> Self {
+        Self::from_base(path, fs::read_to_string(path).ok())
+    }
+
+    /// The state held by `base`, contents of the state file at `path`.
+    pub fn from_base(path: &str, base: Option<String>) -> Self {
+        let mut ledger = Ledger::new();
+        let mut league_state = LeagueState::default();
+        let (wallets, tiers, ratings) = state_file::with_copy(path, base.as_deref(), |copy| {
+            persist::load(copy, &mut ledger, &mut league_state);
+            (MockWalletAdapter::load(copy), TierMap::load(copy), RatingBook::load(copy))
+        });
+
+        Self { base, ledger, league_state, wallets, tiers, ratings }
+    }
+}
+
+/// Settle the run against `state` and stage the resulting state.json.
+/// Writes it only when `commit`; returns it either way.
+fn settle_and_persist(
+    cfg: &Config,
+    mut results: Vec<Standing>,
+    report: &mut RunReport,
+    state: &mut RunState,
     commitments: Vec<(String, [u8; 32])>,
     commit: bool,
-) {
-    results.sort_by(|a, b| b.total_score.cmp(&a.total_score));
-
-    println!("\nsettlement:");
-    for (rank, r) in results.iter().enumerate() {
-        let reward = match rank {
-            0 => 100,
-            1 => 50,
-            _ => 0,
-        };
+    format: OutputFormat,
+) -> String {
+    let path = &cfg.state_path;
+    let before = RunState::from_base(path, state.base.clone());
+    let record = &mut report.run;
+    results.sort_by_key(|r| std::cmp::Reverse(r.total_score));
 
-        println!(
-            "{} rank={} reward={} capacity_before={}",
-            r.agent_id,
-            rank + 1,
-            reward,
-            evolve(ledger, &r.agent_id)
-        );
+    let rewards = record.payout.payouts(&results);
 
-        if commit {
-            ledger.credit(&r.agent_id, reward);
-            wallet_adapter.credit(&r.agent_id, reward);
+    if format.is_text() {
+        println!("\nsettlement: payout={}", record.payout);
+    }
+    for (rank, (r, &reward)) in results.iter().zip(&rewards).enumerate() {
+        let settlement = Settlement {
+            agent: r.agent_id.clone(),
+            rank: rank + 1,
+            reward,
+            capacity_before: evolve(&state.ledger, &r.agent_id) as u64,
+        };
+        if format.is_text() {
+            println!(
+                "{} rank={} reward={} capacity_before={}",
+                settlement.agent, settlement.rank, settlement.reward, settlement.capacity_before
+            );
         }
+        format.event("settlement", &settlement);
+        report.settlement.push(settlement);
+
+        record.results.push(AgentResult {
+            agent: r.agent_id.clone(),
+            rank: rank + 1,
+            total_score: r.total_score,
+            matches: r.matches,
+            reward,
+        });
+
+        state.ledger.credit(&r.agent_id, reward);
+        state.wallets.credit(&r.agent_id, reward);
+    }
+
+    let old_commitments =
+        state_file::with_copy(path, state.base.as_deref(), commitment::load_records);
+    let mut index = old_commitments.clone();
+    for c in &record.commitments {
+        index.insert(c.agent.clone(), c.clone());
+    }
+
+    let moves: Vec<TierMove> = state
+        .tiers
+        .apply(record.league, &results, &state.ledger, &cfg.tiers)
+        .into_iter()
+        .map(|(agent, from, to)| TierMove { agent, from, to })
+        .collect();
+    for m in &moves {
+        format.event("tier_move", m);
+    }
+
+    let outcomes = if record.pairings.is_empty() {
+        ratings::outcomes_from_standings(&results)
+    } else {
+        ratings::outcomes_from_pairings(&record.pairings)
+    };
+    state.ratings.update(&record.system, &outcomes);
+
+    let staged = state_file::stage(path, state.base.as_deref(), |scratch| {
+        state_file::save_core(scratch, &state.ledger, &state.league_state, commitments);
+        state.wallets.save(scratch);
+        commitment::save_records(scratch, &index);
+        state.tiers.save(scratch);
+        state.ratings.save(scratch);
+    });
+
+    report.diff = plan::diff(&before, state, &old_commitments, record, moves);
+
+    if format.is_text() {
+        println!("\nstate changes{}:", if commit { "" } else { " (dry run, not applied)" });
+        plan::print_diff(&report.diff);
     }
 
     if commit {
-        persist::save("state.json", ledger, league_state, commitments);
-        wallet_adapter.save("state.json");
-        println!("\nstate + commitments + wallets committed");
+        state_file::replace(path, &staged);
+        if format.is_text() {
+            println!("\nstate + commitments + wallets committed");
+        }
     }
+
+    staged
 }
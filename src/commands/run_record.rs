@@ -0,0 +1,153 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Serialize, Deserialize};
+
+use crate::commands::commitment::Commitment;
+use crate::commands::league::Tier;
+use crate::commands::payout::PayoutPolicy;
+use crate::commands::tournament::{Format, Pairing};
+use crate::systems::Disqualified;
+
+/* ------------------------------
+   Run record
+-------------------------------*/
+
+/// What a run did and why each agent was paid what it was.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub id: String,
+    /// Unix seconds when the run started.
+    pub started_at: u64,
+    /// False for dry runs kept with `--record`.
+    pub committed: bool,
+    pub nets_core_version: String,
+    pub cli_version: String,
+    pub system: String,
+    pub system_params: serde_json::Value,
+    /// Run seed; per-match seeds derive from it.
+    pub seed: u64,
+    pub league: Tier,
+    pub format: Format,
+    pub payout: PayoutPolicy,
+    pub results: Vec<AgentResult>,
+    /// Pairings played, for formats other than `league`.
+    #[serde(default)]
+    pub pairings: Vec<Pairing>,
+    /// Agents removed from the run, with the reason.
+    #[serde(default)]
+    pub disqualified: Vec<Disqualified>,
+    pub commitments: Vec<Commitment>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AgentResult {
+    pub agent: String,
+    pub rank: usize,
+    pub total_score: i64,
+    pub matches: usize,
+    pub reward: u64,
+}
+
+impl RunRecord {
+    pub fn result(&self, agent: &str) -> Option<&AgentResult> {
+        self.results.iter().find(|r| r.agent == agent)
+    }
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn versions() -> (String, String) {
+    (
+        env!("NETS_CORE_VERSION").to_string(),
+        env!("CARGO_PKG_VERSION").to_string(),
+    )
+}
+
+/* ------------------------------
+   History directory
+-------------------------------*/
+
+/// One `<id>.json` per run; ids are `run-NNNNNN`, increasing. The next id
+/// follows the highest one among the file names, so a record that no
+/// longer parses still holds its id.
+pub fn next_id(dir: &str) -> String {
+    let last = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().into_string().ok()?;
+            name.strip_prefix("run-")?.strip_suffix(".json")?.parse::<u64>().ok()
+        })
+        .max()
+        .unwrap_or(0);
+    format!("run-{:06}", last + 1)
+}
+
+/// Write `record` as a new file; an existing record is never overwritten.
+pub fn save(dir: &str, record: &RunRecord) {
+    fs::create_dir_all(dir).unwrap_or_else(|_| {
+        eprintln!("cannot create history directory: {}", dir);
+        std::process::exit(1);
+    });
+
+    let path = Path::new(dir).join(format!("{}.json", record.id));
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .unwrap_or_else(|e| {
+            eprintln!("cannot write run record {}: {}", path.display(), e);
+            std::process::exit(1);
+        });
+    file.write_all(serde_json::to_string_pretty(record).unwrap().as_bytes()).unwrap();
+}
+
+pub fn load(dir: &str, id: &str) -> Option<RunRecord> {
+    let path = Path::new(dir).join(format!("{}.json", id));
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+}
+
+/// All readable records, oldest first.
+pub fn list(dir: &str) -> Vec<RunRecord> {
+    let mut out: Vec<RunRecord> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("json"))
+        .filter_map(|e| fs::read_to_string(e.path()).ok())
+        .filter_map(|s| serde_json::from_str(&s).ok())
+        .collect();
+    out.sort_by(|a, b| a.id.cmp(&b.id));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_follow_the_file_names_even_when_records_do_not_parse() {
+        let dir = std::env::temp_dir().join(format!("nets-history-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("run-000002.json"), "{}").unwrap();
+        fs::write(dir.join("run-000007.json"), "not json").unwrap();
+        fs::write(dir.join("notes.txt"), "").unwrap();
+
+        let id = next_id(dir.to_str().unwrap());
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(id, "run-000008");
+    }
+}
@@ -1,24 +1,69 @@
+use std::fs;
+
+use serde::Serialize;
+
 use nets::{ledger::Ledger, league_state::LeagueState, persist};
 
+use crate::commands::commitment;
+use crate::commands::output::OutputFormat;
+use crate::commands::state_file;
+use crate::config::Config;
+
 use crate::wallet::mock::MockWalletAdapter;
 use crate::wallet::adapter::WalletAdapter;
 
-pub fn slash(agent: String, amount: u64) {
+/// `nets slash` JSON output.
+#[derive(Serialize)]
+pub struct SlashReport {
+    pub agent: String,
+    pub amount: u64,
+    /// Ledger balance after the slash.
+    pub balance: u64,
+}
+
+pub fn slash(cfg: &Config, agent: String, amount: Option<u64>, format: OutputFormat) {
+    let report = apply(cfg, agent, amount);
+
+    if format.is_text() {
+        println!(
+            "slashed agent {} by {}",
+            report.agent, report.amount
+        );
+    }
+    format.emit("slash", &report);
+}
+
+/// Slash `agent` and persist; `amount` defaults to `slash_amount`.
+pub fn apply(cfg: &Config, agent: String, amount: Option<u64>) -> SlashReport {
+    let amount = amount.unwrap_or(cfg.slash_amount);
+
+    let base = fs::read_to_string(&cfg.state_path).ok();
     let mut ledger = Ledger::new();
     let mut league_state = LeagueState::default();
 
-    let _ = persist::load("state.json", &mut ledger, &mut league_state);
+    let state = persist::load(&cfg.state_path, &mut ledger, &mut league_state);
 
-    let mut wallet_adapter = MockWalletAdapter::load("state.json");
+    let mut wallet_adapter = MockWalletAdapter::load(&cfg.state_path);
 
     ledger.slash(&agent, amount);
     wallet_adapter.slash(&agent, amount);
 
-    persist::save("state.json", &ledger, &league_state, Vec::new());
-    wallet_adapter.save("state.json");
+    // The slashed agent's commitment is void; every other agent keeps its own.
+    let commitments: Vec<(String, [u8; 32])> = state
+        .commitments
+        .into_iter()
+        .filter(|(id, _)| id != &agent)
+        .collect();
+    let mut records = commitment::load_records(&cfg.state_path);
+    records.remove(&agent);
+
+    // Ledger, commitments and wallets land in state.json together or not at all.
+    let staged = state_file::stage(&cfg.state_path, base.as_deref(), |scratch| {
+        state_file::save_core(scratch, &ledger, &league_state, commitments);
+        commitment::save_records(scratch, &records);
+        wallet_adapter.save(scratch);
+    });
+    state_file::replace(&cfg.state_path, &staged);
 
-    println!(
-        "slashed agent {} by {}",
-        agent, amount
-    );
+    SlashReport { balance: ledger.balance(&agent), agent, amount }
 }
@@ -0,0 +1,166 @@
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use nets::{ledger::Ledger, league_state::LeagueState, persist};
+
+/* ------------------------------
+   CLI-owned sections of state.json
+-------------------------------*/
+
+fn read_json(path: &str) -> Value {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_else(|| serde_json::json!({}))
+}
+
+/// Sections earlier versions wrote and nothing reads any more. They are
+/// dropped the next time the state is saved.
+const RETIRED_KEYS: &[&str] = &[
+    // Superseded by the history directory.
+    "last_run",
+];
+
+fn drop_retired(json: &mut Value) {
+    if let Some(fields) = json.as_object_mut() {
+        for key in RETIRED_KEYS {
+            fields.remove(*key);
+        }
+    }
+}
+
+/// Load a CLI-owned section, falling back to its default when absent.
+pub fn load_section<T: DeserializeOwned + Default>(path: &str, key: &str) -> T {
+    read_json(path)
+        .get(key)
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Write a CLI-owned section, leaving every other key untouched.
+pub fn save_section<T: Serialize>(path: &str, key: &str, value: &T) {
+    let mut json = read_json(path);
+    drop_retired(&mut json);
+    json[key] = serde_json::to_value(value).unwrap();
+    fs::write(path, serde_json::to_string_pretty(&json).unwrap()).unwrap();
+}
+
+/// `persist::save`, keeping any sections nets-core does not know about.
+pub fn save_core(
+    path: &str,
+    ledger: &Ledger,
+    league_state: &LeagueState,
+    commitments: Vec<(String, [u8; 32])>,
+) {
+    let before = read_json(path);
+
+    persist::save(path, ledger, league_state, commitments);
+
+    let mut after = read_json(path);
+    if let (Some(before), Some(after)) = (before.as_object(), after.as_object_mut()) {
+        for (k, v) in before {
+            after.entry(k.clone()).or_insert_with(|| v.clone());
+        }
+    }
+    drop_retired(&mut after);
+
+    fs::write(path, serde_json::to_string_pretty(&after).unwrap()).unwrap();
+}
+
+/// A path next to `path` that no other stage or replace in any process
+/// uses, so concurrent runs never read each other's half-written state.
+fn scratch_path(path: &str, suffix: &str) -> String {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let n = NEXT.fetch_add(1, Ordering::Relaxed);
+    format!("{}.{}-{}.{}", path, std::process::id(), n, suffix)
+}
+
+/// Run `f` against a scratch copy of `path` holding `base`, so state
+/// already read into memory can be loaded with the path-based loaders.
+/// `path` itself is not touched.
+pub fn with_copy<T>(path: &str, base: Option<&str>, f: impl FnOnce(&str) -> T) -> T {
+    let scratch = scratch_path(path, "staged");
+    if let Some(b) = base {
+        fs::write(&scratch, b).unwrap();
+    }
+
+    let out = f(&scratch);
+
+    let _ = fs::remove_file(&scratch);
+    out
+}
+
+/// Run `write` against a scratch copy of `path` holding `base`, and return
+/// the file it leaves behind. `path` itself is not touched.
+pub fn stage(path: &str, base: Option<&str>, write: impl FnOnce(&str)) -> String {
+    with_copy(path, base, |scratch| {
+        write(scratch);
+        fs::read_to_string(scratch).unwrap_or_default()
+    })
+}
+
+/// Replace `path` with `contents` in one step: readers see either the old
+/// file or the new one, never a partial write.
+pub fn replace(path: &str, contents: &str) {
+    let tmp = scratch_path(path, "tmp");
+    fs::write(&tmp, contents).unwrap();
+    fs::rename(&tmp, path).unwrap_or_else(|e| {
+        let _ = fs::remove_file(&tmp);
+        eprintln!("failed to write {}: {}", path, e);
+        std::process::exit(1);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saving_drops_the_retired_last_run_section() {
+        let path = std::env::temp_dir().join(format!("nets-state-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        fs::write(path, r#"{"last_run": {"id": "r1"}, "ratings": {"rps": 1}}"#).unwrap();
+
+        save_section(path, "tiers", &serde_json::json!({"a": "gold"}));
+        let json = read_json(path);
+        let _ = fs::remove_file(path);
+
+        assert!(json.get("last_run").is_none());
+        assert_eq!(json["ratings"], serde_json::json!({"rps": 1}));
+        assert_eq!(json["tiers"], serde_json::json!({"a": "gold"}));
+    }
+
+    #[test]
+    fn concurrent_stages_do_not_share_a_scratch_file() {
+        let path = std::env::temp_dir().join(format!("nets-stage-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let outer = stage(path, Some("{}"), |a| {
+            let inner = stage(path, None, |b| {
+                assert_ne!(a, b);
+                fs::write(b, "inner").unwrap();
+            });
+            assert_eq!(inner, "inner");
+            fs::write(a, "outer").unwrap();
+        });
+
+        assert_eq!(outer, "outer");
+        assert!(fs::metadata(path).is_err());
+    }
+
+    #[test]
+    fn replace_swaps_the_whole_file() {
+        let path = std::env::temp_dir().join(format!("nets-replace-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        fs::write(path, "old").unwrap();
+
+        replace(path, "new");
+        let contents = fs::read_to_string(path).unwrap();
+        let _ = fs::remove_file(path);
+
+        assert_eq!(contents, "new");
+    }
+}
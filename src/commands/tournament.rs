@@ -0,0 +1,638 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Serialize, Deserialize};
+
+use nets::{ledger::Ledger, match_trace::TraceStep};
+
+use crate::systems::{
+    guest::Module, parallel, match_seed, AgentTraces, Disqualified, LeagueOutcome, LeagueSpec,
+    PlayedMatch, Standing, SystemPlugin,
+};
+
+/* ------------------------------
+   Formats
+-------------------------------*/
+
+/// How agents are paired in a run.
+///
+/// Outside `league`, a pairing is `matches` games in which both agents play
+/// the same seeded instance; the higher score wins the game.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "kebab-case")]
+pub enum Format {
+    /// `league`: nets-core league scoring, no pairings.
+    League,
+    /// `round-robin`: every agent meets every other agent once.
+    RoundRobin,
+    /// `swiss:<rounds>`: agents on equal points meet, no rematches.
+    Swiss { rounds: usize },
+    /// `single-elim`: seeded knockout bracket.
+    SingleElim,
+    /// `double-elim`: knocked out after the second lost pairing.
+    DoubleElim,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (name, args) = s.split_once(':').unwrap_or((s, ""));
+
+        match name {
+            "league" => Ok(Format::League),
+            "round-robin" => Ok(Format::RoundRobin),
+            "swiss" => match args.parse() {
+                Ok(rounds) if rounds > 0 => Ok(Format::Swiss { rounds }),
+                _ => Err("swiss expects a positive round count, e.g. swiss:5".into()),
+            },
+            "single-elim" => Ok(Format::SingleElim),
+            "double-elim" => Ok(Format::DoubleElim),
+            _ => Err(format!(
+                "unknown format '{}' (known: league, round-robin, swiss:<rounds>, single-elim, double-elim)",
+                name
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::League => f.write_str("league"),
+            Format::RoundRobin => f.write_str("round-robin"),
+            Format::Swiss { rounds } => write!(f, "swiss:{}", rounds),
+            Format::SingleElim => f.write_str("single-elim"),
+            Format::DoubleElim => f.write_str("double-elim"),
+        }
+    }
+}
+
+/* ------------------------------
+   Pairings
+-------------------------------*/
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Pairing {
+    pub round: usize,
+    /// `main`, `losers` or `final` in double elimination, else `main`.
+    pub bracket: String,
+    pub a: String,
+    /// `None` is a bye for `a`.
+    pub b: Option<String>,
+    pub score_a: i64,
+    pub score_b: i64,
+    pub winner: Option<String>,
+}
+
+pub struct TournamentOutcome {
+    pub league: LeagueOutcome,
+    pub pairings: Vec<Pairing>,
+}
+
+/// Plays game `g` of agent `p`: its score, trace root and trace steps, or
+/// why the agent is disqualified.
+type PlayGame<'a> = dyn Fn(usize, u64) -> Result<(i64, Played), String> + Sync + 'a;
+
+/// Trace root and steps of one game.
+type Played = ([u8; 32], Vec<TraceStep>);
+
+/// Scores per (agent, game index), filled one round at a time so that
+/// every game of a round runs through the worker pool together.
+struct ScoreBook<'a> {
+    /// Agent ids, indexed like the run's agents.
+    agents: &'a [String],
+    /// Games per pairing.
+    matches: usize,
+    jobs: usize,
+    play: &'a PlayGame<'a>,
+    scores: BTreeMap<(usize, u64), i64>,
+    /// Trace of every game played, committed per agent.
+    played: BTreeMap<(usize, u64), Played>,
+    /// Disqualified agents and why; they lose every pairing from then on.
+    faults: BTreeMap<usize, String>,
+}
+
+impl<'a> ScoreBook<'a> {
+    fn new(
+        agents: &'a [String],
+        matches: usize,
+        jobs: usize,
+        play: &'a PlayGame<'a>,
+    ) -> Self {
+        Self {
+            agents,
+            matches: matches.max(1),
+            jobs,
+            play,
+            scores: BTreeMap::new(),
+            played: BTreeMap::new(),
+            faults: BTreeMap::new(),
+        }
+    }
+
+    fn games(&self, round: usize) -> std::ops::Range<u64> {
+        let per = self.matches as u64;
+        round as u64 * per..(round as u64 + 1) * per
+    }
+
+    fn ensure(&mut self, round: usize, players: &[usize]) {
+        let todo: Vec<(usize, u64)> = players
+            .iter()
+            .flat_map(|&p| self.games(round).map(move |g| (p, g)))
+            .filter(|k| !self.scores.contains_key(k))
+            .collect();
+
+        let play = self.play;
+        let played = parallel::map(&todo, self.jobs, |&(p, g)| play(p, g));
+
+        for (&(p, g), result) in todo.iter().zip(played) {
+            match result {
+                Ok((score, trace)) => {
+                    self.scores.insert((p, g), score);
+                    self.played.insert((p, g), trace);
+                }
+                Err(reason) => {
+                    self.scores.insert((p, g), 0);
+                    self.faults.entry(p).or_insert(reason);
+                }
+            }
+        }
+    }
+
+    /// Game wins for `a` and `b` plus their summed scores over a round.
+    fn pair(&mut self, round: usize, a: usize, b: usize) -> (usize, usize, i64, i64) {
+        self.ensure(round, &[a, b]);
+
+        let (mut wins_a, mut wins_b, mut sum_a, mut sum_b) = (0, 0, 0, 0);
+        for g in self.games(round) {
+            let (sa, sb) = (self.scores[&(a, g)], self.scores[&(b, g)]);
+            sum_a += sa;
+            sum_b += sb;
+            if sa > sb {
+                wins_a += 1;
+            } else if sb > sa {
+                wins_b += 1;
+            }
+        }
+
+        // A disqualified agent forfeits every game against a sound one.
+        let games = self.games(round).count();
+        match (self.faults.contains_key(&a), self.faults.contains_key(&b)) {
+            (true, false) => (0, games, sum_a, sum_b),
+            (false, true) => (games, 0, sum_a, sum_b),
+            _ => (wins_a, wins_b, sum_a, sum_b),
+        }
+    }
+}
+
+/* ------------------------------
+   Execution
+-------------------------------*/
+
+/// Run `agents` (already in seeding order) under `format`.
+pub fn play(
+    format: &Format,
+    plugin: &dyn SystemPlugin,
+    spec: &LeagueSpec,
+    agents: &[(String, Module)],
+    ledger: &Ledger,
+) -> TournamentOutcome {
+    if *format == Format::League {
+        return TournamentOutcome {
+            league: plugin.run_league(spec, agents, ledger),
+            pairings: Vec::new(),
+        };
+    }
+
+    let game = |p: usize, g: u64| {
+        let (id, module) = &agents[p];
+        plugin
+            .play(
+                spec.params,
+                match_seed(spec.seed, g),
+                &[(id.as_str(), module)],
+                ledger,
+                spec.league,
+                &spec.limits,
+            )
+            .remove(0)
+            .map(|(score, trace)| (score, (trace.merkle.root(), trace.steps)))
+    };
+    let ids: Vec<String> = agents.iter().map(|(id, _)| id.clone()).collect();
+    let mut book = ScoreBook::new(&ids, spec.matches, spec.jobs, &game);
+
+    let all: Vec<usize> = (0..agents.len()).collect();
+    // Every agent plays round 0, whatever the bracket.
+    book.ensure(0, &all);
+
+    let mut pairings = Vec::new();
+    let ranking = match format {
+        Format::League => unreachable!(),
+        Format::RoundRobin => round_robin(&mut book, &mut pairings),
+        Format::Swiss { rounds } => swiss(&mut book, *rounds, &mut pairings),
+        Format::SingleElim => elimination(&mut book, 1, &mut pairings),
+        Format::DoubleElim => elimination(&mut book, 2, &mut pairings),
+    };
+
+    let mut games: BTreeMap<&str, usize> = BTreeMap::new();
+    for p in pairings.iter().filter(|p| p.b.is_some()) {
+        *games.entry(p.a.as_str()).or_insert(0) += spec.matches.max(1);
+        *games.entry(p.b.as_deref().unwrap()).or_insert(0) += spec.matches.max(1);
+    }
+
+    let standings = ranking
+        .into_iter()
+        .filter(|(p, _)| !book.faults.contains_key(p))
+        .map(|(p, points)| Standing {
+            agent_id: agents[p].0.clone(),
+            total_score: points,
+            matches: games.get(agents[p].0.as_str()).copied().unwrap_or(0),
+        })
+        .collect();
+
+    let commitments = all
+        .iter()
+        .filter(|&&p| !book.faults.contains_key(&p))
+        .map(|&p| AgentTraces {
+            agent: agents[p].0.clone(),
+            matches: book
+                .played
+                .range((p, 0)..(p + 1, 0))
+                .map(|(&(_, g), (root, steps))| PlayedMatch {
+                    seed: match_seed(spec.seed, g),
+                    root: *root,
+                    steps: steps.clone(),
+                    lineup: Vec::new(),
+                })
+                .collect(),
+        })
+        .collect();
+
+    let disqualified = book
+        .faults
+        .iter()
+        .map(|(&p, reason)| Disqualified { agent: agents[p].0.clone(), reason: reason.clone() })
+        .collect();
+
+    TournamentOutcome {
+        league: LeagueOutcome {
+            standings,
+            commitments,
+            disqualified,
+        },
+        pairings,
+    }
+}
+
+/// Play one pairing and record it.
+///
+/// The agent with more game wins takes the pairing. With `draws` an even
+/// split is a draw (`None`); without, the higher summed score and then the
+/// higher seed (lower index) advance.
+fn decide(
+    book: &mut ScoreBook,
+    round: usize,
+    bracket: &str,
+    (a, b): (usize, usize),
+    draws: bool,
+    pairings: &mut Vec<Pairing>,
+) -> Option<usize> {
+    let (wa, wb, sa, sb) = book.pair(round, a, b);
+
+    let winner = match wa.cmp(&wb) {
+        Ordering::Greater => Some(a),
+        Ordering::Less => Some(b),
+        Ordering::Equal if draws => None,
+        Ordering::Equal => match sa.cmp(&sb) {
+            Ordering::Greater => Some(a),
+            Ordering::Less => Some(b),
+            Ordering::Equal => Some(a.min(b)),
+        },
+    };
+
+    pairings.push(Pairing {
+        round: round + 1,
+        bracket: bracket.into(),
+        a: book.agents[a].clone(),
+        b: Some(book.agents[b].clone()),
+        score_a: sa,
+        score_b: sb,
+        winner: winner.map(|w| book.agents[w].clone()),
+    });
+
+    winner
+}
+
+fn bye(book: &ScoreBook, round: usize, bracket: &str, a: usize, pairings: &mut Vec<Pairing>) {
+    pairings.push(Pairing {
+        round: round + 1,
+        bracket: bracket.into(),
+        a: book.agents[a].clone(),
+        b: None,
+        score_a: 0,
+        score_b: 0,
+        winner: Some(book.agents[a].clone()),
+    });
+}
+
+/// Best-first by points; ties keep seeding order.
+fn rank_by_points(points: &[i64]) -> Vec<(usize, i64)> {
+    let mut ranking: Vec<(usize, i64)> = points.iter().copied().enumerate().collect();
+    ranking.sort_by_key(|&(_, p)| std::cmp::Reverse(p));
+    ranking
+}
+
+/// 2 points for a pairing won, 1 each for a draw.
+fn award(points: &mut [i64], (a, b): (usize, usize), winner: Option<usize>) {
+    match winner {
+        Some(w) => points[w] += 2,
+        None => {
+            points[a] += 1;
+            points[b] += 1;
+        }
+    }
+}
+
+fn round_robin(book: &mut ScoreBook, pairings: &mut Vec<Pairing>) -> Vec<(usize, i64)> {
+    let n = book.agents.len();
+    let mut points = vec![0; n];
+
+    for a in 0..n {
+        for b in a + 1..n {
+            let winner = decide(book, 0, "main", (a, b), true, pairings);
+            award(&mut points, (a, b), winner);
+        }
+    }
+
+    rank_by_points(&points)
+}
+
+fn swiss(book: &mut ScoreBook, rounds: usize, pairings: &mut Vec<Pairing>) -> Vec<(usize, i64)> {
+    let n = book.agents.len();
+    let mut points = vec![0; n];
+    let mut met: BTreeSet<(usize, usize)> = BTreeSet::new();
+    let mut had_bye = vec![false; n];
+
+    for round in 0..rounds {
+        let mut order: Vec<usize> = rank_by_points(&points).into_iter().map(|(p, _)| p).collect();
+
+        // Lowest-ranked agent without a bye sits out, scoring a win.
+        if order.len() % 2 == 1 {
+            let pos = order
+                .iter()
+                .rposition(|&p| !had_bye[p])
+                .unwrap_or(order.len() - 1);
+            let p = order.remove(pos);
+            had_bye[p] = true;
+            points[p] += 2;
+            bye(book, round, "main", p, pairings);
+        }
+
+        book.ensure(round, &order);
+
+        // Pair each agent with the next-ranked one it has not met yet.
+        while !order.is_empty() {
+            let a = order.remove(0);
+            let pos = order
+                .iter()
+                .position(|&b| !met.contains(&(a.min(b), a.max(b))))
+                .unwrap_or(0);
+            let b = order.remove(pos);
+
+            met.insert((a.min(b), a.max(b)));
+            let winner = decide(book, round, "main", (a, b), true, pairings);
+            award(&mut points, (a, b), winner);
+        }
+    }
+
+    rank_by_points(&points)
+}
+
+/// Standard bracket order for `size` slots (a power of two): seed 1 meets
+/// seed `size`, and the top two seeds can only meet in the final.
+fn bracket_order(size: usize) -> Vec<usize> {
+    let mut order = vec![0];
+    while order.len() < size {
+        let n = order.len() * 2;
+        order = order.iter().flat_map(|&s| [s, n - 1 - s]).collect();
+    }
+    order
+}
+
+/// Knockout where an agent is out after `lives` lost pairings.
+///
+/// Undefeated agents play the seeded main bracket. With two lives, agents
+/// on one loss play a losers bracket in the order they dropped into it;
+/// the last agent of each meets in the final, which is replayed if the
+/// main bracket's agent loses it.
+fn elimination(book: &mut ScoreBook, lives: usize, pairings: &mut Vec<Pairing>) -> Vec<(usize, i64)> {
+    let n = book.agents.len();
+
+    let mut losses = vec![0usize; n];
+    let mut out_order: Vec<usize> = Vec::new();
+
+    // `None` slots pad the bracket to a power of two and give byes.
+    let mut main: Vec<Option<usize>> = bracket_order(n.next_power_of_two())
+        .into_iter()
+        .map(|s| (s < n).then_some(s))
+        .collect();
+    let mut losers: Vec<usize> = Vec::new();
+
+    for round in 0.. {
+        let main_alive: Vec<usize> = main.iter().flatten().copied().collect();
+        if main_alive.len() + losers.len() <= 1 {
+            break;
+        }
+
+        let playing: Vec<usize> = main_alive.iter().chain(&losers).copied().collect();
+        book.ensure(round, &playing);
+
+        let mut beaten = Vec::new();
+
+        if main_alive.len() == 1 && losers.len() == 1 {
+            let pair = (main_alive[0], losers[0]);
+            let w = decide(book, round, "final", pair, false, pairings).unwrap();
+            beaten.push(if w == pair.0 { pair.1 } else { pair.0 });
+        } else {
+            if main.len() > 1 {
+                let mut next = Vec::new();
+                for slot in main.chunks(2) {
+                    next.push(match (slot[0], slot[1]) {
+                        (Some(a), Some(b)) => {
+                            let w = decide(book, round, "main", (a, b), false, pairings).unwrap();
+                            beaten.push(if w == a { b } else { a });
+                            Some(w)
+                        }
+                        (Some(a), None) | (None, Some(a)) => {
+                            bye(book, round, "main", a, pairings);
+                            Some(a)
+                        }
+                        (None, None) => None,
+                    });
+                }
+                main = next;
+            }
+
+            if losers.len() > 1 {
+                // Once the main bracket is empty this is the reset final.
+                let label = if main_alive.is_empty() && losers.len() == 2 { "final" } else { "losers" };
+                let mut next = Vec::new();
+                for pair in losers.chunks(2) {
+                    match *pair {
+                        [a, b] => {
+                            let w = decide(book, round, label, (a, b), false, pairings).unwrap();
+                            beaten.push(if w == a { b } else { a });
+                            next.push(w);
+                        }
+                        [a] => {
+                            bye(book, round, label, a, pairings);
+                            next.push(a);
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+                losers = next;
+            }
+        }
+
+        for p in beaten {
+            losses[p] += 1;
+            if losses[p] >= lives {
+                out_order.push(p);
+            } else if !losers.contains(&p) {
+                losers.push(p);
+            }
+        }
+
+        // The main bracket's last agent leaves it on losing the final.
+        if main.len() == 1 && !matches!(main[0], Some(p) if losses[p] == 0) {
+            main.clear();
+        }
+    }
+
+    // Champion first, then in reverse order of elimination. Placement is
+    // the score, so settlement ranks agents by how far they got.
+    let champion = (0..n).find(|&p| losses[p] < lives);
+    let ranking: Vec<usize> = champion.into_iter().chain(out_order.into_iter().rev()).collect();
+
+    let len = ranking.len() as i64;
+    ranking
+        .into_iter()
+        .enumerate()
+        .map(|(i, p)| (p, len - i as i64))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agents(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("a{}", i)).collect()
+    }
+
+    /// Play `run` with one game per pairing, scored by `score(agent, round)`.
+    fn with_book<T>(
+        n: usize,
+        score: impl Fn(usize, u64) -> i64 + Sync,
+        run: impl FnOnce(&mut ScoreBook, &mut Vec<Pairing>) -> T,
+    ) -> (T, Vec<Pairing>) {
+        let agents = agents(n);
+        let play = |p: usize, g: u64| Ok((score(p, g), ([0; 32], Vec::new())));
+        let mut book = ScoreBook::new(&agents, 1, 1, &play);
+        let mut pairings = Vec::new();
+        let out = run(&mut book, &mut pairings);
+        (out, pairings)
+    }
+
+    fn met(pairings: &[Pairing]) -> Vec<(String, String)> {
+        pairings
+            .iter()
+            .filter_map(|p| {
+                let b = p.b.clone()?;
+                Some(if p.a < b { (p.a.clone(), b) } else { (b, p.a.clone()) })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn swiss_pairs_equal_points_without_rematches() {
+        // Lower index is stronger.
+        let (ranking, pairings) =
+            with_book(4, |p, _| 10 - p as i64, |book, out| swiss(book, 3, out));
+
+        assert_eq!(ranking, vec![(0, 6), (1, 4), (2, 2), (3, 0)]);
+        let mut pairs = met(&pairings);
+        assert_eq!(pairs.len(), 6);
+        pairs.sort();
+        pairs.dedup();
+        assert_eq!(pairs.len(), 6, "no pair meets twice");
+        // Round 2 pairs the two round-1 winners.
+        assert_eq!((pairings[2].a.as_str(), pairings[2].b.as_deref()), ("a0", Some("a2")));
+    }
+
+    #[test]
+    fn swiss_gives_each_agent_at_most_one_bye() {
+        let (ranking, pairings) =
+            with_book(3, |p, _| 10 - p as i64, |book, out| swiss(book, 3, out));
+
+        let byes: Vec<&str> =
+            pairings.iter().filter(|p| p.b.is_none()).map(|p| p.a.as_str()).collect();
+        assert_eq!(byes, vec!["a2", "a1", "a0"]);
+        assert_eq!(ranking, vec![(0, 6), (1, 4), (2, 2)]);
+    }
+
+    #[test]
+    fn bracket_order_keeps_top_seeds_apart() {
+        assert_eq!(bracket_order(4), vec![0, 3, 1, 2]);
+        assert_eq!(bracket_order(8), vec![0, 7, 3, 4, 1, 6, 2, 5]);
+    }
+
+    #[test]
+    fn single_elim_gives_the_top_seed_a_bye() {
+        let (ranking, pairings) =
+            with_book(3, |p, _| 10 - p as i64, |book, out| elimination(book, 1, out));
+
+        assert_eq!((pairings[0].a.as_str(), pairings[0].b.as_deref()), ("a0", None));
+        assert_eq!(ranking, vec![(0, 3), (1, 2), (2, 1)]);
+    }
+
+    #[test]
+    fn double_elim_replays_the_final_when_the_main_bracket_loses() {
+        // a0 wins round 1, a1 wins the final, a0 wins the reset.
+        let score = |p: usize, g: u64| match (p, g) {
+            (0, 1) | (1, 0) | (1, 2) => 1,
+            _ => 2,
+        };
+        let (ranking, pairings) = with_book(2, score, |book, out| elimination(book, 2, out));
+
+        let played: Vec<(&str, Option<&str>)> = pairings
+            .iter()
+            .map(|p| (p.bracket.as_str(), p.winner.as_deref()))
+            .collect();
+        assert_eq!(
+            played,
+            vec![("main", Some("a0")), ("final", Some("a1")), ("final", Some("a0"))]
+        );
+        assert_eq!(ranking, vec![(0, 2), (1, 1)]);
+    }
+
+    #[test]
+    fn disqualified_agents_forfeit_their_pairings() {
+        let agents = agents(2);
+        let play = |p: usize, _| match p {
+            0 => Err("trapped".into()),
+            _ => Ok((0, ([0; 32], Vec::new()))),
+        };
+        let mut book = ScoreBook::new(&agents, 1, 1, &play);
+        let mut pairings = Vec::new();
+
+        let ranking = round_robin(&mut book, &mut pairings);
+        assert_eq!(pairings[0].winner.as_deref(), Some("a1"));
+        assert_eq!(ranking[0], (1, 2));
+        assert_eq!(book.faults.get(&0).map(String::as_str), Some("trapped"));
+    }
+}
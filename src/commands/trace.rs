@@ -0,0 +1,260 @@
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+
+use crate::cli::TraceCommand;
+use crate::commands::commitment;
+use crate::commands::pgn::{Board, Color, Game, Move, Ply};
+use crate::commands::trace_file::TraceFile;
+use crate::config::Config;
+use crate::systems::{self, console::Frame};
+
+/* ------------------------------
+   nets trace
+-------------------------------*/
+
+/// `nets trace export --to`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceFormat {
+    Pgn,
+}
+
+impl FromStr for TraceFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "pgn" => Ok(TraceFormat::Pgn),
+            _ => Err(format!("unknown trace format '{}' (known: pgn)", s)),
+        }
+    }
+}
+
+impl fmt::Display for TraceFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceFormat::Pgn => f.write_str("pgn"),
+        }
+    }
+}
+
+pub fn handle(cfg: &Config, cmd: TraceCommand) {
+    match cmd {
+        TraceCommand::Export { trace, agent, match_index, to, agent_wasm, out } => {
+            let (file, source) = match (trace, agent, match_index) {
+                (Some(path), _, _) => (read_trace(&path), path),
+                (None, Some(agent), Some(index)) => {
+                    let source = format!("{}'s committed match {}", agent, index);
+                    (committed_match(cfg, &agent, index), source)
+                }
+                _ => {
+                    eprintln!("pass --trace, or --agent with --match");
+                    std::process::exit(1);
+                }
+            };
+            export(cfg, &file, &source, to, agent_wasm, out)
+        }
+    }
+}
+
+fn read_trace(path: &str) -> TraceFile {
+    let data = fs::read_to_string(path).unwrap_or_else(|_| {
+        eprintln!("trace file not found: {}", path);
+        std::process::exit(1);
+    });
+    serde_json::from_str(&data).unwrap_or_else(|_| {
+        eprintln!("invalid trace format");
+        std::process::exit(1);
+    })
+}
+
+/// Match `index` of the agent's last committed run, as a trace file.
+fn committed_match(cfg: &Config, agent: &str, index: usize) -> TraceFile {
+    let record = commitment::load_records(&cfg.state_path)
+        .remove(agent)
+        .unwrap_or_else(|| {
+            eprintln!("no committed run recorded for agent {}", agent);
+            std::process::exit(1);
+        });
+    let file = record.match_file(&cfg.traces_dir, index).unwrap_or_else(|| {
+        eprintln!(
+            "agent {} committed to {} match(es); there is no match {}",
+            agent,
+            record.committed_matches().len(),
+            index
+        );
+        std::process::exit(1);
+    });
+    file.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Trace files and commitments only hold hashes, so the match is replayed
+/// from the agent's wasm; the export is refused unless the replay
+/// reproduces the recorded root.
+fn export(
+    cfg: &Config,
+    file: &TraceFile,
+    source: &str,
+    to: TraceFormat,
+    agent_wasm: Option<String>,
+    out: Option<String>,
+) {
+    if to == TraceFormat::Pgn && file.system != "chess" {
+        eprintln!("pgn export needs a chess trace, not {}", file.system);
+        std::process::exit(1);
+    }
+
+    let wasm_path = agent_wasm
+        .unwrap_or_else(|| cfg.agent_wasm(&file.agent).to_string_lossy().to_string());
+    let wasm = fs::read(&wasm_path).unwrap_or_else(|_| {
+        eprintln!("agent wasm not found: {}", wasm_path);
+        std::process::exit(1);
+    });
+
+    // Shared boards replay the whole table the match was recorded with.
+    let (seats, seat) = commitment::seat_table(
+        &file.agent,
+        &file.lineup,
+        &wasm,
+        commitment::workspace_wasm(cfg),
+    )
+    .unwrap_or_else(|e| {
+        eprintln!("cannot replay agent {}: {}", file.agent, e);
+        std::process::exit(1);
+    });
+    let table: Vec<(&str, &[u8])> =
+        seats.iter().map(|(id, w)| (id.as_str(), w.as_slice())).collect();
+
+    let plugin = systems::resolve(&file.system);
+    let (frames, trace) = plugin
+        .record(&file.system_params, file.seed, &table, seat, &file.limits, file.encoding)
+        .unwrap_or_else(|e| {
+            eprintln!("cannot replay agent {}: {}", file.agent, e);
+            std::process::exit(1);
+        });
+    if hex::encode(trace.merkle.root()) != file.merkle_root {
+        eprintln!("replay does not match {}; is {} the agent's wasm?", source, wasm_path);
+        std::process::exit(1);
+    }
+
+    let steps: Vec<u64> = trace.steps.iter().map(|s| s.step).collect();
+    let text = chess_game(file, &frames, &steps)
+        .unwrap_or_else(|e| {
+            eprintln!("cannot export {}: {}", source, e);
+            std::process::exit(1);
+        })
+        .to_pgn();
+
+    match out {
+        Some(path) => {
+            fs::write(&path, text).unwrap();
+            println!("{} written to {}", to, path);
+        }
+        None => print!("{}", text),
+    }
+}
+
+const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// The game behind a chess trace. The agent's moves come from the frames;
+/// any move the system made between two of them is the legal move that
+/// leads from one observed position to the next.
+fn chess_game(file: &TraceFile, frames: &[Frame], steps: &[u64]) -> Result<Game, String> {
+    let position = |i: usize| {
+        let fen = frames[i].position.as_deref().ok_or("observation has no FEN")?;
+        Board::from_fen(fen)
+    };
+    if frames.is_empty() {
+        return Err("the agent made no moves".into());
+    }
+
+    let start = position(0)?;
+    let mut board = start.clone();
+    let mut plies = Vec::new();
+    let (mut agent_white, mut agent_black) = (false, false);
+    let mut ending = None;
+
+    for (i, frame) in frames.iter().enumerate() {
+        let at = position(i)?;
+        if at.key() != board.key() {
+            let reply = board
+                .legal_moves()
+                .into_iter()
+                .find(|&m| {
+                    let mut next = board.clone();
+                    next.apply(m);
+                    next.key() == at.key()
+                })
+                .ok_or_else(|| {
+                    format!("no legal move leads to the position at step {}", steps[i])
+                })?;
+            plies.push(Ply { san: board.san(reply), comment: None });
+        }
+        board = at;
+
+        match board.side {
+            Color::White => agent_white = true,
+            Color::Black => agent_black = true,
+        }
+
+        match Move::from_uci(&frame.action).filter(|m| board.legal_moves().contains(m)) {
+            Some(m) => {
+                plies.push(Ply { san: board.san(m), comment: None });
+                board.apply(m);
+            }
+            None => {
+                ending = Some((
+                    format!("step {}: illegal move {}", steps[i], frame.action),
+                    board.side,
+                ));
+                break;
+            }
+        }
+    }
+
+    // An illegal move forfeits; otherwise only mate and stalemate are
+    // decided, anything else (e.g. the ply limit) is left open.
+    let loser = match &ending {
+        Some((note, side)) => {
+            if let Some(ply) = plies.last_mut() {
+                ply.comment = Some(note.clone());
+            }
+            Some(*side)
+        }
+        None if board.legal_moves().is_empty() && board.in_check() => Some(board.side),
+        None => None,
+    };
+    let result = match loser {
+        Some(Color::White) => "0-1",
+        Some(Color::Black) => "1-0",
+        None if board.legal_moves().is_empty() => "1/2-1/2",
+        None => "*",
+    };
+
+    let name = |played: bool| if played { file.agent.clone() } else { "nets-core".to_string() };
+    let mut tags = vec![
+        ("Event".to_string(), format!("nets {} match", file.system)),
+        ("Site".to_string(), "nets".to_string()),
+        ("Date".to_string(), "????.??.??".to_string()),
+        ("Round".to_string(), "-".to_string()),
+        ("White".to_string(), name(agent_white)),
+        ("Black".to_string(), name(agent_black)),
+        ("Result".to_string(), result.to_string()),
+        ("Seed".to_string(), file.seed.to_string()),
+    ];
+    if start.key() != Board::from_fen(START)?.key() {
+        tags.push(("SetUp".to_string(), "1".to_string()));
+        tags.push(("FEN".to_string(), frames[0].position.clone().unwrap_or_default()));
+    }
+
+    Ok(Game {
+        tags,
+        comment: Some(format!("commitment root {}", file.merkle_root)),
+        start,
+        plies,
+        result: result.to_string(),
+    })
+}
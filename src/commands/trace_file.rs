@@ -0,0 +1,122 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Serialize, Deserialize};
+
+use nets::match_trace::{MatchTrace, TraceStep};
+
+use crate::commands::commitment::Commitment;
+use crate::systems::guest::{Limits, OBS_ENCODING};
+
+/* ------------------------------
+   Trace files
+-------------------------------*/
+
+/// A match trace written to disk, with everything needed to replay it.
+#[derive(Serialize, Deserialize)]
+pub struct TraceFile {
+    pub system: String,
+    pub agent: String,
+    pub system_params: serde_json::Value,
+    pub seed: u64,
+    /// Recorded since limits existed; older traces were played unmetered.
+    #[serde(default = "Limits::unmetered")]
+    pub limits: Limits,
+    /// Observation encoding; see `Commitment::encoding`.
+    #[serde(default)]
+    pub encoding: u32,
+    pub merkle_root: String,
+    /// Every agent at the table in seat order, for shared boards.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lineup: Vec<String>,
+    pub steps: Vec<TraceFileStep>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TraceFileStep {
+    pub step: u64,
+    pub obs_hash: String,
+    pub action_hash: String,
+}
+
+impl TraceFile {
+    pub fn new(
+        system: &str,
+        agent: &str,
+        system_params: &serde_json::Value,
+        seed: u64,
+        limits: &Limits,
+        trace: &MatchTrace,
+    ) -> Self {
+        Self {
+            system: system.to_string(),
+            agent: agent.to_string(),
+            system_params: system_params.clone(),
+            seed,
+            limits: *limits,
+            encoding: OBS_ENCODING,
+            merkle_root: hex::encode(trace.merkle.root()),
+            lineup: Vec::new(),
+            steps: step_records(&trace.steps),
+        }
+    }
+
+    /// The commitment a verifier would check this trace against.
+    pub fn commitment(&self) -> Commitment {
+        Commitment {
+            system: self.system.clone(),
+            agent: self.agent.clone(),
+            merkle_root: self.merkle_root.clone(),
+            system_params: self.system_params.clone(),
+            seed: self.seed,
+            limits: self.limits,
+            encoding: self.encoding,
+            lineup: self.lineup.clone(),
+            steps: self.steps.clone(),
+            matches: Vec::new(),
+        }
+    }
+}
+
+/// Trace steps with their hashes in hex, as trace files and commitments
+/// store them.
+pub fn step_records(steps: &[TraceStep]) -> Vec<TraceFileStep> {
+    steps
+        .iter()
+        .map(|s| TraceFileStep {
+            step: s.step,
+            obs_hash: hex::encode(s.obs_hash),
+            action_hash: hex::encode(s.action_hash),
+        })
+        .collect()
+}
+
+/* ------------------------------
+   Committed match traces
+-------------------------------*/
+
+/// Name of `agent`'s trace file for the committed match played with
+/// `seed`, whose trace root is `root` (hex).
+pub fn committed_name(agent: &str, seed: u64, root: &str) -> String {
+    format!("{}-{}-{}.json", agent, seed, root)
+}
+
+/// Write `file` to `dir` as `name`, creating the directory if needed.
+pub fn write_committed(dir: &str, name: &str, file: &TraceFile) {
+    fs::create_dir_all(dir).unwrap();
+    fs::write(Path::new(dir).join(name), serde_json::to_string_pretty(file).unwrap()).unwrap();
+}
+
+/// Steps of the committed trace file `name` in `dir`, refused unless the
+/// file records the committed `root`.
+pub fn committed_steps(dir: &str, name: &str, root: &str) -> Result<Vec<TraceFileStep>, String> {
+    let path = Path::new(dir).join(name);
+    let data = fs::read_to_string(&path)
+        .map_err(|_| format!("trace file not found: {}", path.display()))?;
+    let file: TraceFile = serde_json::from_str(&data)
+        .map_err(|_| format!("invalid trace format: {}", path.display()))?;
+    if file.merkle_root != root {
+        return Err(format!("trace file {} is not of the committed match", path.display()));
+    }
+    Ok(file.steps)
+}
@@ -1,69 +1,37 @@
 use std::fs;
-use std::path::Path;
 
 use nets::{
-    system::System,
-    agent::Agent,
-    snake::{SnakeSystem, Dir},
-    wasm_agent::WasmAgent,
-    match_trace::run_match_with_trace,
     ledger::Ledger,
     league_state::LeagueState,
     persist,
 };
 
-/* ------------------------------
-   Action decoding
--------------------------------*/
+use serde::Serialize;
 
-fn u64_to_dir(v: u64) -> Dir {
-    match v % 4 {
-        0 => Dir::Up,
-        1 => Dir::Down,
-        2 => Dir::Left,
-        _ => Dir::Right,
-    }
-}
+use crate::commands::commitment;
+use crate::commands::output::OutputFormat;
+use crate::config::Config;
 
 /* ------------------------------
-   WASM Snake Adapter
+   nets verify (commitment-aware)
 -------------------------------*/
 
-struct SnakeWasmAgent {
-    inner: WasmAgent,
+/// `nets verify` JSON output; a mismatch also exits non-zero.
+#[derive(Serialize)]
+pub struct VerifyReport {
+    pub valid: bool,
+    pub agent: String,
+    pub system: String,
+    pub seed: u64,
+    pub expected_root: String,
+    pub recomputed_root: String,
+    /// First committed match that replayed differently.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mismatched_match: Option<usize>,
 }
 
-impl Agent<
-    <SnakeSystem as System>::Observation,
-    <SnakeSystem as System>::Action,
-> for SnakeWasmAgent {
-    fn id(&self) -> String {
-        self.inner.id.clone()
-    }
-
-    fn decide(
-        &mut self,
-        obs: <SnakeSystem as System>::Observation,
-    ) -> <SnakeSystem as System>::Action {
-        let ((hx, hy), (ax, ay), _) = obs;
-
-        let packed =
-            ((hx as u64 & 0xFF) << 24)
-            | ((hy as u64 & 0xFF) << 16)
-            | ((ax as u64 & 0xFF) << 8)
-            | (ay as u64 & 0xFF);
-
-        u64_to_dir(self.inner.decide(packed))
-    }
-}
-
-/* ------------------------------
-   nets verify (commitment-aware)
--------------------------------*/
-
-pub fn verify(agent: String) {
-    let agents_dir = Path::new("agents");
-    let wasm_path = agents_dir.join(format!("{}.wasm", agent));
+pub fn verify(cfg: &Config, agent: String, system: Option<String>, format: OutputFormat) {
+    let wasm_path = cfg.agent_wasm(&agent);
 
     if !wasm_path.exists() {
         eprintln!("agent wasm not found: {:?}", wasm_path);
@@ -74,7 +42,7 @@ pub fn verify(agent: String) {
 
     let mut ledger = Ledger::new();
     let mut league_state = LeagueState::default();
-    let state = persist::load("state.json", &mut ledger, &mut league_state);
+    let state = persist::load(&cfg.state_path, &mut ledger, &mut league_state);
 
     let expected_root = state
         .commitments
@@ -86,25 +54,46 @@ pub fn verify(agent: String) {
             std::process::exit(1);
         });
 
-    let mut agent_instance = SnakeWasmAgent {
-        inner: WasmAgent::load(agent.clone(), &wasm)
-            .expect("failed to load agent"),
-    };
+    // Replay the recorded matches; fall back to one match with the
+    // defaults for roots committed before those were recorded.
+    let record = commitment::for_root(cfg, &agent, expected_root, system);
 
-    let system = SnakeSystem::new(10, 10, 300);
-    let trace = run_match_with_trace(system, &mut agent_instance);
-    let recomputed_root = trace.merkle.root();
+    let opponent = commitment::workspace_wasm(cfg);
+    let replay = commitment::replay(&record, &wasm, opponent).unwrap_or_else(|e| {
+        eprintln!("cannot replay agent {}: {}", agent, e);
+        std::process::exit(1);
+    });
+    let recomputed_root = replay.root;
+    let valid = recomputed_root == expected_root && replay.mismatch.is_none();
+
+    format.emit(
+        "verify",
+        &VerifyReport {
+            valid,
+            agent: agent.clone(),
+            system: record.system.clone(),
+            seed: record.seed,
+            expected_root: hex::encode(expected_root),
+            recomputed_root: hex::encode(recomputed_root),
+            mismatched_match: replay.mismatch,
+        },
+    );
 
-    if recomputed_root != expected_root {
+    if !valid {
         eprintln!(
             "verification failed: merkle root mismatch\nexpected={:x?}\nactual={:x?}",
             expected_root, recomputed_root
         );
+        if let Some(m) = replay.mismatch {
+            eprintln!("first differing match: {}", m);
+        }
         std::process::exit(1);
     }
 
-    println!(
-        "verification ok: agent={} merkle_root={:x?}",
-        agent, recomputed_root
-    );
+    if format.is_text() {
+        println!(
+            "verification ok: agent={} merkle_root={:x?}",
+            agent, recomputed_root
+        );
+    }
 }
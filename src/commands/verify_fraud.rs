@@ -1,10 +1,26 @@
 use std::fs;
 
+use serde::Serialize;
+
 use nets::fraud::verify_step_proof;
 
 use crate::commands::commitment::{Commitment, FraudProofEnvelope};
+use crate::commands::output::OutputFormat;
+
+/// `nets verify-fraud` JSON output. `valid` means the proof shows the
+/// commitment is inconsistent; an invalid proof also exits non-zero.
+#[derive(Serialize)]
+pub struct VerifyFraudReport {
+    pub valid: bool,
+    pub agent: String,
+    pub system: String,
+    pub committed_root: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_index: Option<usize>,
+    pub step_index: u64,
+}
 
-pub fn verify_fraud(commitment_path: String, proof_path: String) {
+pub fn verify_fraud(commitment_path: String, proof_path: String, format: OutputFormat) {
     let commitment_data = fs::read_to_string(&commitment_path)
         .unwrap_or_else(|_| {
             eprintln!("commitment file not found: {}", commitment_path);
@@ -29,15 +45,52 @@ pub fn verify_fraud(commitment_path: String, proof_path: String) {
             std::process::exit(1);
         });
 
-    let committed_root: [u8; 32] = hex::decode(&commitment.merkle_root)
-        .unwrap()
-        .try_into()
-        .unwrap();
+    let committed_root = decode_root(&commitment.merkle_root);
+
+    // A proof about one match of a run is checked against that match's
+    // root, which must be the one the commitment aggregates.
+    let valid = match (envelope.match_index, &envelope.match_root) {
+        (Some(index), Some(match_root)) => {
+            let roots: Vec<[u8; 32]> = commitment
+                .matches
+                .iter()
+                .map(|m| decode_root(&m.merkle_root))
+                .collect();
+            commitment.matches.get(index).map(|m| &m.merkle_root) == Some(match_root)
+                && commitment.root_of(&roots) == committed_root
+                && verify_step_proof(decode_root(match_root), &envelope.proof)
+        }
+        _ => commitment.matches.is_empty() && verify_step_proof(committed_root, &envelope.proof),
+    };
 
-    if verify_step_proof(committed_root, &envelope.proof) {
-        println!("VALID FRAUD PROOF: commitment is inconsistent");
+    format.emit(
+        "verify_fraud",
+        &VerifyFraudReport {
+            valid,
+            agent: commitment.agent,
+            system: commitment.system,
+            committed_root: commitment.merkle_root,
+            match_index: envelope.match_index,
+            step_index: envelope.proof.step_index,
+        },
+    );
+
+    if valid {
+        if format.is_text() {
+            println!("VALID FRAUD PROOF: commitment is inconsistent");
+        }
     } else {
         eprintln!("INVALID FRAUD PROOF");
         std::process::exit(1);
     }
 }
+
+fn decode_root(hex_root: &str) -> [u8; 32] {
+    hex::decode(hex_root)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .unwrap_or_else(|| {
+            eprintln!("invalid merkle root hex: {}", hex_root);
+            std::process::exit(1);
+        })
+}
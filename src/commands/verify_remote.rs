@@ -1,16 +1,32 @@
 use std::fs;
 
-use nets::{
-    snake::SnakeSystem,
-    match_trace::run_match_with_trace,
-};
+use serde::Serialize;
 
-use crate::commands::{
-    commitment::Commitment,
-    snake_agent::SnakeWasmAgent,
-};
+use crate::commands::commitment::{self, Commitment};
+use crate::commands::output::OutputFormat;
+use crate::config::Config;
 
-pub fn verify_remote(commitment_path: String, agent_wasm: String) {
+/// `nets verify-remote` JSON output; a mismatch also exits non-zero.
+#[derive(Serialize)]
+pub struct VerifyRemoteReport {
+    pub valid: bool,
+    pub agent: String,
+    pub system: String,
+    pub seed: u64,
+    pub committed_root: String,
+    pub recomputed_root: String,
+    /// First committed match that replayed differently.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mismatched_match: Option<usize>,
+}
+
+/// Opponents of shared-board matches are read from the workspace.
+pub fn verify_remote(
+    cfg: &Config,
+    commitment_path: String,
+    agent_wasm: String,
+    format: OutputFormat,
+) {
     let data = fs::read_to_string(&commitment_path)
         .expect("failed to read commitment file");
     let commitment: Commitment =
@@ -18,24 +34,38 @@ pub fn verify_remote(commitment_path: String, agent_wasm: String) {
 
     let wasm = fs::read(&agent_wasm).expect("failed to read agent wasm");
 
-    let mut agent =
-        SnakeWasmAgent::load(commitment.agent.clone(), &wasm);
-
-    let system = SnakeSystem::new(
-        commitment.system_params.width,
-        commitment.system_params.height,
-        commitment.system_params.steps,
-    );
-
-    let trace = run_match_with_trace(system, &mut agent);
-    let root = trace.merkle.root();
+    let opponent = commitment::workspace_wasm(cfg);
+    let replay = commitment::replay(&commitment, &wasm, opponent).unwrap_or_else(|e| {
+        eprintln!("cannot replay agent {}: {}", commitment.agent, e);
+        std::process::exit(1);
+    });
+    let root = replay.root;
     let expected = hex::decode(&commitment.merkle_root)
         .expect("invalid merkle root hex");
+    let valid = root[..] == expected[..] && replay.mismatch.is_none();
+
+    format.emit(
+        "verify_remote",
+        &VerifyRemoteReport {
+            valid,
+            agent: commitment.agent,
+            system: commitment.system,
+            seed: commitment.seed,
+            committed_root: commitment.merkle_root,
+            recomputed_root: hex::encode(root),
+            mismatched_match: replay.mismatch,
+        },
+    );
 
-    if root[..] != expected[..] {
-        eprintln!("FRAUD: merkle root mismatch");
+    if !valid {
+        match replay.mismatch {
+            Some(m) => eprintln!("FRAUD: merkle root mismatch in match {}", m),
+            None => eprintln!("FRAUD: merkle root mismatch"),
+        }
         std::process::exit(1);
     }
 
-    println!("VALID: commitment verified");
+    if format.is_text() {
+        println!("VALID: commitment verified");
+    }
 }
@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::commands::league::{Tier, TierRules};
+use crate::commands::payout::PayoutPolicy;
+use crate::systems::guest::Limits;
+
+/* ------------------------------
+   Workspace config (nets.toml)
+-------------------------------*/
+
+/// Resolved workspace settings.
+///
+/// Precedence, lowest to highest: built-in defaults, `nets.toml`,
+/// `NETS_*` environment variables, then per-command CLI flags (applied by
+/// the commands themselves).
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub state_path: String,
+    pub agents_dir: String,
+    /// One JSON record per run, read by `nets history`.
+    pub history_dir: String,
+    /// Per-match trace files of committed runs, read by `prove-fraud`.
+    pub traces_dir: String,
+    pub default_system: String,
+    pub league: Tier,
+    /// `[tiers]` promotion and relegation rules.
+    pub tiers: TierRules,
+    /// `[payout]` table; defaults to `top-n` paying 100 and 50.
+    pub payout: PayoutPolicy,
+    pub default_wallet: String,
+    /// Agent ids or glob patterns never selected by `nets run`.
+    pub exclude: Vec<String>,
+    pub slash_amount: u64,
+    /// `[limits]` fuel and memory budgets for every agent.
+    pub limits: Limits,
+    /// Per-system parameter defaults, e.g. `[params.snake]`.
+    pub params: BTreeMap<String, toml::Table>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            state_path: "state.json".into(),
+            agents_dir: "agents".into(),
+            history_dir: "history".into(),
+            traces_dir: "traces".into(),
+            default_system: "snake".into(),
+            league: Tier::Bronze,
+            tiers: TierRules::default(),
+            payout: PayoutPolicy::default(),
+            default_wallet: "default".into(),
+            exclude: Vec::new(),
+            slash_amount: 50,
+            limits: Limits::default(),
+            params: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Load the config for a workspace.
+    ///
+    /// The workspace is `workspace`, else `NETS_WORKSPACE`, else the current
+    /// directory. The config file is `config`, else `NETS_CONFIG`, else
+    /// `<workspace>/nets.toml`; a missing default file is not an error.
+    pub fn load(workspace: Option<String>, config: Option<String>) -> Self {
+        let root = PathBuf::from(
+            workspace
+                .or_else(|| env::var("NETS_WORKSPACE").ok())
+                .unwrap_or_else(|| ".".into()),
+        );
+
+        let explicit = config.or_else(|| env::var("NETS_CONFIG").ok());
+        let path = explicit
+            .clone()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| root.join("nets.toml"));
+
+        let mut cfg = match fs::read_to_string(&path) {
+            Ok(s) => toml::from_str(&s).unwrap_or_else(|e| {
+                eprintln!("invalid config {}: {}", path.display(), e);
+                std::process::exit(1);
+            }),
+            Err(_) if explicit.is_none() => Config::default(),
+            Err(_) => {
+                eprintln!("config file not found: {}", path.display());
+                std::process::exit(1);
+            }
+        };
+
+        cfg.apply_env();
+
+        cfg.state_path = resolve(&root, &cfg.state_path);
+        cfg.agents_dir = resolve(&root, &cfg.agents_dir);
+        cfg.history_dir = resolve(&root, &cfg.history_dir);
+        cfg.traces_dir = resolve(&root, &cfg.traces_dir);
+        cfg
+    }
+
+    fn apply_env(&mut self) {
+        if let Ok(v) = env::var("NETS_STATE") {
+            self.state_path = v;
+        }
+        if let Ok(v) = env::var("NETS_AGENTS_DIR") {
+            self.agents_dir = v;
+        }
+        if let Ok(v) = env::var("NETS_HISTORY_DIR") {
+            self.history_dir = v;
+        }
+        if let Ok(v) = env::var("NETS_TRACES_DIR") {
+            self.traces_dir = v;
+        }
+        if let Ok(v) = env::var("NETS_SYSTEM") {
+            self.default_system = v;
+        }
+        if let Ok(v) = env::var("NETS_LEAGUE") {
+            self.league = v.parse().unwrap_or_else(|e| {
+                eprintln!("invalid NETS_LEAGUE: {}", e);
+                std::process::exit(1);
+            });
+        }
+        if let Ok(v) = env::var("NETS_WALLET") {
+            self.default_wallet = v;
+        }
+        if let Ok(v) = env::var("NETS_SLASH_AMOUNT") {
+            self.slash_amount = v.parse().unwrap_or_else(|_| {
+                eprintln!("invalid NETS_SLASH_AMOUNT '{}'", v);
+                std::process::exit(1);
+            });
+        }
+        if let Ok(v) = env::var("NETS_PAYOUT") {
+            self.payout = v.parse().unwrap_or_else(|e| {
+                eprintln!("invalid NETS_PAYOUT '{}': {}", v, e);
+                std::process::exit(1);
+            });
+        }
+    }
+
+    /// The `--system` flag if given, else the workspace default.
+    pub fn system(&self, flag: Option<String>) -> String {
+        flag.unwrap_or_else(|| self.default_system.clone())
+    }
+
+    /// Parameter defaults for `system` from `[params.<system>]`.
+    pub fn system_params(&self, system: &str) -> Option<serde_json::Value> {
+        self.params
+            .get(system)
+            .map(|t| serde_json::to_value(t).unwrap())
+    }
+
+    pub fn agent_wasm(&self, agent: &str) -> PathBuf {
+        Path::new(&self.agents_dir).join(format!("{}.wasm", agent))
+    }
+}
+
+/// Relative paths, from the file or the environment, are relative to the
+/// workspace root.
+fn resolve(root: &Path, path: &str) -> String {
+    let p = Path::new(path);
+    if p.is_absolute() {
+        path.to_string()
+    } else {
+        root.join(p).to_string_lossy().to_string()
+    }
+}
@@ -1,5 +1,7 @@
 mod cli;
+mod config;
 mod commands;
+mod systems;
 mod wallet;
 
 use clap::Parser;
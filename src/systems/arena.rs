@@ -0,0 +1,346 @@
+use nets::{
+    agent::Agent,
+    match_trace::{MatchTrace, TraceStep},
+    merkle::MerkleTree,
+    snake::Dir,
+};
+
+use super::match_seed;
+
+/* ------------------------------
+   Rules
+-------------------------------*/
+
+// Several snakes share one board:
+//
+// - every snake starts one cell long on a free cell drawn from the seed,
+//   in seat order, and then one food is drawn the same way
+// - each step every live snake is shown the board and answers with a
+//   direction; all snakes then move at once
+// - a head that reaches the food alone eats it: the snake grows by one
+//   and a new food is drawn from the free cells
+// - heads that meet on one cell, the food cell included, all die, so
+//   contended food is never eaten; so do two heads that swap cells
+// - a head that leaves the board or enters a body dies; a tail that
+//   moves away this step frees its cell, one that grows does not
+// - dead snakes leave the board at once
+// - a snake scores 1 for each step it survives and FOOD_POINTS per food
+//
+// The match ends after `steps` steps or when no snake is alive.
+
+/// Points for each food eaten.
+pub const FOOD_POINTS: i64 = 10;
+
+pub type Cell = (i32, i32);
+
+/// One snake's view of the board.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArenaObservation {
+    pub head: Cell,
+    /// The snake's own head when the board has no free cell for food.
+    pub food: Cell,
+    /// Heads of the other live snakes, in seat order.
+    pub opponents: Vec<Cell>,
+}
+
+impl ArenaObservation {
+    /// Bytes hashed into the trace: head, food, opponent count and
+    /// opponent heads, as little-endian `i32`s and a `u32`.
+    fn bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(20 + 8 * self.opponents.len());
+        for (x, y) in [self.head, self.food] {
+            out.extend_from_slice(&x.to_le_bytes());
+            out.extend_from_slice(&y.to_le_bytes());
+        }
+        out.extend_from_slice(&(self.opponents.len() as u32).to_le_bytes());
+        for &(x, y) in &self.opponents {
+            out.extend_from_slice(&x.to_le_bytes());
+            out.extend_from_slice(&y.to_le_bytes());
+        }
+        out
+    }
+}
+
+#[derive(Clone)]
+pub struct SnakeArenaSystem {
+    width: i32,
+    height: i32,
+    steps: u64,
+    seed: u64,
+}
+
+impl SnakeArenaSystem {
+    pub fn new(width: i32, height: i32, steps: u64) -> Self {
+        Self { width, height, steps, seed: 0 }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+}
+
+/// A move as `(code, dx, dy)`; codes are the guest's, as in `u64_to_dir`.
+fn step_of(dir: Dir) -> (u8, i32, i32) {
+    match dir {
+        Dir::Up => (0, 0, -1),
+        Dir::Down => (1, 0, 1),
+        Dir::Left => (2, -1, 0),
+        Dir::Right => (3, 1, 0),
+    }
+}
+
+struct Board {
+    system: SnakeArenaSystem,
+    /// Each seat's body, head first; `None` once it has died.
+    snakes: Vec<Option<Vec<Cell>>>,
+    food: Option<Cell>,
+    draws: u64,
+}
+
+impl Board {
+    fn new(system: SnakeArenaSystem, seats: usize) -> Self {
+        let mut board = Self { system, snakes: Vec::new(), food: None, draws: 0 };
+        for _ in 0..seats {
+            let start = board.draw();
+            board.snakes.push(start.map(|cell| vec![cell]));
+        }
+        board.food = board.draw();
+        board
+    }
+
+    /// A free cell picked with the next draw from the seed, or `None` if
+    /// the board is full.
+    fn draw(&mut self) -> Option<Cell> {
+        let taken: Vec<Cell> = self.snakes.iter().flatten().flatten().copied().collect();
+        let free: Vec<Cell> = (0..self.system.height)
+            .flat_map(|y| (0..self.system.width).map(move |x| (x, y)))
+            .filter(|cell| !taken.contains(cell))
+            .collect();
+        let pick = match_seed(self.system.seed, self.draws);
+        self.draws += 1;
+        (!free.is_empty()).then(|| free[(pick % free.len() as u64) as usize])
+    }
+
+    fn observe(&self, seat: usize) -> Option<ArenaObservation> {
+        let head = self.snakes[seat].as_ref()?[0];
+        let opponents = self
+            .snakes
+            .iter()
+            .enumerate()
+            .filter(|&(other, _)| other != seat)
+            .filter_map(|(_, snake)| snake.as_ref().map(|body| body[0]))
+            .collect();
+        Some(ArenaObservation { head, food: self.food.unwrap_or(head), opponents })
+    }
+
+    fn on_board(&self, (x, y): Cell) -> bool {
+        (0..self.system.width).contains(&x) && (0..self.system.height).contains(&y)
+    }
+
+    /// Move every live snake one cell, `moves[seat]` giving its step.
+    /// Returns the seats that ate.
+    fn advance(&mut self, moves: &[Option<(i32, i32)>]) -> Vec<usize> {
+        let before: Vec<Option<Cell>> =
+            self.snakes.iter().map(|snake| Some(snake.as_ref()?[0])).collect();
+        let heads: Vec<Option<Cell>> = self
+            .snakes
+            .iter()
+            .zip(moves)
+            .map(|(snake, step)| {
+                let (x, y) = snake.as_ref()?[0];
+                let (dx, dy) = (*step)?;
+                Some((x + dx, y + dy))
+            })
+            .collect();
+        let meets = |cell: Cell| heads.iter().filter(|&&h| h == Some(cell)).count();
+        let swaps = |seat: usize| {
+            (0..heads.len())
+                .any(|other| heads[other] == before[seat] && heads[seat] == before[other])
+        };
+
+        let eaters: Vec<usize> = (0..heads.len())
+            .filter(|&seat| heads[seat].is_some_and(|h| Some(h) == self.food && meets(h) == 1))
+            .collect();
+
+        for (seat, head) in heads.iter().enumerate() {
+            if let (Some(body), Some(head)) = (self.snakes[seat].as_mut(), head) {
+                body.insert(0, *head);
+                if !eaters.contains(&seat) {
+                    body.pop();
+                }
+            }
+        }
+
+        let dead: Vec<usize> = (0..heads.len())
+            .filter(|&seat| {
+                let Some(head) = heads[seat] else { return false };
+                !self.on_board(head)
+                    || meets(head) > 1
+                    || swaps(seat)
+                    || self.snakes.iter().flatten().any(|body| body[1..].contains(&head))
+            })
+            .collect();
+        for seat in dead {
+            self.snakes[seat] = None;
+        }
+
+        if !eaters.is_empty() {
+            self.food = self.draw();
+        }
+        eaters
+    }
+
+    fn alive(&self) -> bool {
+        self.snakes.iter().any(Option::is_some)
+    }
+}
+
+/// Play one arena match with every agent of `table`, returning each
+/// seat's score and trace. A seat's trace holds the observations it was
+/// shown and the moves it made, up to the step it died in.
+pub fn run_arena_with_traces<A>(
+    system: SnakeArenaSystem,
+    table: &mut [A],
+) -> (Vec<i64>, Vec<MatchTrace>)
+where
+    A: Agent<ArenaObservation, Dir>,
+{
+    let steps = system.steps;
+    let mut board = Board::new(system, table.len());
+    let mut scores = vec![0i64; table.len()];
+    let mut traces: Vec<Vec<TraceStep>> = table.iter().map(|_| Vec::new()).collect();
+
+    for step in 0..steps {
+        if !board.alive() {
+            break;
+        }
+        let moves: Vec<Option<(i32, i32)>> = table
+            .iter_mut()
+            .enumerate()
+            .map(|(seat, agent)| {
+                let obs = board.observe(seat)?;
+                let obs_hash = *blake3::hash(&obs.bytes()).as_bytes();
+                let (code, dx, dy) = step_of(agent.decide(obs));
+                let action_hash = *blake3::hash(&[code]).as_bytes();
+                traces[seat].push(TraceStep { step, obs_hash, action_hash });
+                Some((dx, dy))
+            })
+            .collect();
+
+        let eaters = board.advance(&moves);
+        for (seat, snake) in board.snakes.iter().enumerate() {
+            if snake.is_some() {
+                scores[seat] += 1;
+            }
+        }
+        for seat in eaters {
+            scores[seat] += FOOD_POINTS;
+        }
+    }
+
+    let traces = traces
+        .into_iter()
+        .map(|steps| {
+            let leaves = steps.iter().map(TraceStep::hash).collect();
+            MatchTrace { merkle: MerkleTree::from_leaves(leaves), steps }
+        })
+        .collect();
+    (scores, traces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(snakes: Vec<Vec<Cell>>, food: Cell) -> Board {
+        Board {
+            system: SnakeArenaSystem::new(8, 8, 10),
+            snakes: snakes.into_iter().map(Some).collect(),
+            food: Some(food),
+            draws: 0,
+        }
+    }
+
+    const UP: Option<(i32, i32)> = Some((0, -1));
+    const LEFT: Option<(i32, i32)> = Some((-1, 0));
+    const RIGHT: Option<(i32, i32)> = Some((1, 0));
+
+    #[test]
+    fn a_lone_head_on_the_food_eats_and_grows() {
+        let mut b = board(vec![vec![(3, 3)], vec![(6, 6)]], (4, 3));
+        assert_eq!(b.advance(&[RIGHT, UP]), vec![0]);
+        assert_eq!(b.snakes[0], Some(vec![(4, 3), (3, 3)]));
+        assert_ne!(b.food, Some((4, 3)));
+    }
+
+    #[test]
+    fn contended_food_kills_every_head_that_reaches_it() {
+        let mut b = board(vec![vec![(3, 3)], vec![(5, 3)], vec![(0, 0)]], (4, 3));
+        assert!(b.advance(&[RIGHT, LEFT, RIGHT]).is_empty());
+        assert_eq!(b.snakes[0], None);
+        assert_eq!(b.snakes[1], None);
+        assert!(b.snakes[2].is_some());
+        assert_eq!(b.food, Some((4, 3)));
+    }
+
+    #[test]
+    fn walls_and_bodies_kill() {
+        let mut b = board(vec![vec![(0, 3)], vec![(4, 4), (4, 5), (4, 6)], vec![(3, 5)]], (7, 7));
+        b.advance(&[LEFT, UP, RIGHT]);
+        assert_eq!(b.snakes[0], None);
+        assert_eq!(b.snakes[1], Some(vec![(4, 3), (4, 4), (4, 5)]));
+        assert_eq!(b.snakes[2], None);
+    }
+
+    #[test]
+    fn a_tail_frees_its_cell_unless_the_snake_grows() {
+        let mut b = board(vec![vec![(4, 4), (4, 5)], vec![(3, 5)]], (4, 3));
+        b.advance(&[UP, RIGHT]);
+        assert_eq!(b.snakes[0], Some(vec![(4, 3), (4, 4), (4, 5)]));
+        assert_eq!(b.snakes[1], None);
+
+        let mut b = board(vec![vec![(4, 4), (4, 5)], vec![(3, 5)]], (7, 7));
+        b.advance(&[UP, RIGHT]);
+        assert_eq!(b.snakes[1], Some(vec![(4, 5)]));
+    }
+
+    #[test]
+    fn heads_passing_through_each_other_both_die() {
+        let mut b = board(vec![vec![(3, 3)], vec![(4, 3)]], (7, 7));
+        b.advance(&[RIGHT, LEFT]);
+        assert!(!b.alive());
+    }
+
+    struct Fixed(Dir);
+
+    impl Agent<ArenaObservation, Dir> for Fixed {
+        fn id(&self) -> String {
+            "fixed".into()
+        }
+
+        fn decide(&mut self, _obs: ArenaObservation) -> Dir {
+            match self.0 {
+                Dir::Up => Dir::Up,
+                Dir::Down => Dir::Down,
+                Dir::Left => Dir::Left,
+                Dir::Right => Dir::Right,
+            }
+        }
+    }
+
+    #[test]
+    fn matches_are_seeded_and_traces_end_when_a_snake_dies() {
+        let play = |seed| {
+            let mut table = vec![Fixed(Dir::Up), Fixed(Dir::Down)];
+            run_arena_with_traces(SnakeArenaSystem::new(8, 8, 20).with_seed(seed), &mut table)
+        };
+        let (scores, traces) = play(3);
+        let roots: Vec<_> = traces.iter().map(|t| t.merkle.root()).collect();
+        let (again, traces_again) = play(3);
+        assert_eq!(scores, again);
+        assert_eq!(roots, traces_again.iter().map(|t| t.merkle.root()).collect::<Vec<_>>());
+
+        // Heading straight for a wall of an 8 by 8 board, neither lasts 8 steps.
+        assert!(traces.iter().all(|t| t.steps.len() <= 8));
+    }
+}
@@ -0,0 +1,214 @@
+use serde::{Serialize, Deserialize};
+
+use nets::{
+    ledger::Ledger,
+    league::League,
+    match_trace::MatchTrace,
+    agent::Agent,
+    chess::ChessSystem,
+    chess::system::ChessObservation,
+    chess::r#move::ChessMove,
+};
+
+use super::console::Console;
+use super::guest::{Guest, Limits, Metered, Module};
+
+pub struct ChessWasmAgent {
+    inner: Guest,
+    /// Observation encoding the agent is played with; see `OBS_ENCODING`.
+    encoding: u32,
+}
+
+impl ChessWasmAgent {
+    pub fn load(
+        agent_id: String,
+        module: &Module,
+        limits: &Limits,
+        encoding: u32,
+    ) -> Result<Self, String> {
+        let mut inner = Guest::load(agent_id, module, limits)?;
+        if encoding >= 1 {
+            inner = inner.require_obs_buffer("chess")?;
+        }
+        Ok(Self { inner, encoding })
+    }
+}
+
+impl Metered for ChessWasmAgent {
+    fn guest(&mut self) -> &mut Guest {
+        &mut self.inner
+    }
+}
+
+fn u64_to_chess_move(v: u64) -> ChessMove {
+    let from = (v & 0b111111) as u8;
+    let to = ((v >> 6) & 0b111111) as u8;
+    let promo = ((v >> 12) & 0b1111) as u8;
+    ChessMove { from, to, promotion: promo }
+}
+
+impl Agent<ChessObservation, ChessMove> for ChessWasmAgent {
+    fn id(&self) -> String {
+        self.inner.id()
+    }
+
+    /// The guest gets the position as FEN in `NETS_OBS`, or nothing under
+    /// encoding 0; see the README's chess section for the move encoding
+    /// it returns.
+    fn decide(&mut self, obs: ChessObservation) -> ChessMove {
+        let decided = match self.encoding {
+            0 => self.inner.decide(0),
+            _ => self.inner.decide_bytes(obs.fen().as_bytes()),
+        };
+        // A forfeited agent submits the null move, which the system rejects.
+        decided.map(u64_to_chess_move).unwrap_or(ChessMove { from: 0, to: 0, promotion: 0 })
+    }
+}
+
+/* ------------------------------
+   Terminal view
+-------------------------------*/
+
+/// Promotion codes in `ChessMove::promotion`, by index.
+const PROMOTIONS: [char; 5] = ['-', 'n', 'b', 'r', 'q'];
+
+/// Square index (`a1` = 0, `h8` = 63) of a square name like `e4`.
+fn square(name: &[u8]) -> Option<u8> {
+    match name {
+        [f @ b'a'..=b'h', r @ b'1'..=b'8'] => Some((r - b'1') * 8 + (f - b'a')),
+        _ => None,
+    }
+}
+
+fn square_name(sq: u8) -> String {
+    format!("{}{}", (b'a' + sq % 8) as char, (b'1' + sq / 8 % 8) as char)
+}
+
+/// The board from White's side, from the observation's FEN; moves are
+/// typed in UCI notation.
+pub struct ChessConsole;
+
+impl Console<ChessObservation, ChessMove> for ChessConsole {
+    fn render(&self, obs: &ChessObservation) -> String {
+        let fen = obs.fen();
+        let mut fields = fen.split_whitespace();
+        let placement = fields.next().unwrap_or("");
+        let side = if fields.next() == Some("b") { "black" } else { "white" };
+
+        let mut board = String::new();
+        for (i, rank) in placement.split('/').enumerate() {
+            board.push_str(&format!("{} ", 8 - i));
+            for c in rank.chars() {
+                match c.to_digit(10) {
+                    Some(n) => (0..n).for_each(|_| board.push_str(". ")),
+                    None => board.push_str(&format!("{} ", c)),
+                }
+            }
+            board.push('\n');
+        }
+        board.push_str(&format!("  a b c d e f g h\n{} to move", side));
+        board
+    }
+
+    fn parse(&self, input: &str) -> Result<ChessMove, String> {
+        let b = input.as_bytes();
+        let promotion = match b.get(4) {
+            None => Some(0),
+            Some(&p) => PROMOTIONS[1..].iter().position(|&c| c as u8 == p).map(|i| i as u8 + 1),
+        };
+        match (b.len(), b.get(0..2).and_then(square), b.get(2..4).and_then(square), promotion) {
+            (4 | 5, Some(from), Some(to), Some(promotion)) => Ok(ChessMove { from, to, promotion }),
+            _ => Err(format!("not a UCI move: '{}' (e.g. e2e4, e7e8q)", input)),
+        }
+    }
+
+    fn show(&self, m: &ChessMove) -> String {
+        let promotion = PROMOTIONS.get(m.promotion as usize).filter(|&&c| c != '-');
+        format!(
+            "{}{}{}",
+            square_name(m.from),
+            square_name(m.to),
+            promotion.map(|c| c.to_string()).unwrap_or_default()
+        )
+    }
+
+    fn help(&self) -> &'static str {
+        "move in UCI, e.g. e2e4 or e7e8q"
+    }
+
+    fn position(&self, obs: &ChessObservation) -> Option<String> {
+        Some(obs.fen())
+    }
+}
+
+/* ------------------------------
+   Registration
+-------------------------------*/
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChessParams {
+    pub max_plies: u64,
+}
+
+impl Default for ChessParams {
+    fn default() -> Self {
+        Self { max_plies: 200 }
+    }
+}
+
+pub struct Chess;
+
+impl super::GameSystem for Chess {
+    const NAME: &'static str = "chess";
+    const MIN_ABI: u32 = 2;
+
+    type Params = ChessParams;
+    type Sys = ChessSystem;
+    type Obs = ChessObservation;
+    type Act = ChessMove;
+    type Adapter = ChessWasmAgent;
+
+    fn validate(p: &ChessParams) -> Result<(), String> {
+        if p.max_plies == 0 {
+            return Err("max_plies must be positive".into());
+        }
+        Ok(())
+    }
+
+    fn build(p: &ChessParams, seed: u64) -> ChessSystem {
+        ChessSystem::new(p.max_plies).with_seed(seed)
+    }
+
+    fn load_agent(
+        agent_id: String,
+        module: &Module,
+        limits: &Limits,
+        encoding: u32,
+    ) -> Result<ChessWasmAgent, String> {
+        ChessWasmAgent::load(agent_id, module, limits, encoding)
+    }
+
+    fn play<A>(
+        system: ChessSystem,
+        table: &mut [A],
+        ledger: &Ledger,
+        league: &League,
+    ) -> Vec<(i64, usize, MatchTrace)>
+    where
+        A: Agent<ChessObservation, ChessMove>,
+    {
+        super::play_solo(system, table, ledger, league)
+    }
+
+    fn replay<A>(system: ChessSystem, table: &mut [A]) -> Vec<MatchTrace>
+    where
+        A: Agent<ChessObservation, ChessMove>,
+    {
+        super::replay_solo(system, table)
+    }
+
+    fn console(_params: &ChessParams) -> Option<Box<dyn Console<ChessObservation, ChessMove>>> {
+        Some(Box::new(ChessConsole))
+    }
+}
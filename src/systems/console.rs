@@ -0,0 +1,149 @@
+use std::io::{self, BufRead, Write};
+
+use nets::agent::Agent;
+
+use super::guest::{Guest, Metered};
+
+/* ------------------------------
+   Terminal view (nets play)
+-------------------------------*/
+
+/// How a system is shown to and played by a person at the terminal.
+pub trait Console<O, A> {
+    /// The board or state the player sees before choosing a move.
+    fn render(&self, obs: &O) -> String;
+    /// Parse a typed move.
+    fn parse(&self, input: &str) -> Result<A, String>;
+    /// A move as the player would type it.
+    fn show(&self, action: &A) -> String;
+    /// How to type a move, e.g. `w/a/s/d`.
+    fn help(&self) -> &'static str;
+    /// The position in a standard notation (FEN for chess), if any.
+    fn position(&self, _obs: &O) -> Option<String> {
+        None
+    }
+}
+
+/// Print `text`, read one line; `q` or end of input quits.
+pub fn prompt(text: &str) -> String {
+    print!("{} > ", text);
+    io::stdout().flush().unwrap();
+
+    let mut line = String::new();
+    let read = io::stdin().lock().read_line(&mut line).unwrap_or(0);
+    if read == 0 || line.trim() == "q" {
+        println!("quit");
+        std::process::exit(0);
+    }
+    line.trim().to_string()
+}
+
+/// The person at the terminal, playing through the system's console.
+pub struct Human<'a, O, A> {
+    console: &'a dyn Console<O, A>,
+    step: u64,
+}
+
+impl<'a, O, A> Human<'a, O, A> {
+    pub fn new(console: &'a dyn Console<O, A>) -> Self {
+        Self { console, step: 0 }
+    }
+}
+
+impl<O, A> Agent<O, A> for Human<'_, O, A> {
+    fn id(&self) -> String {
+        "human".into()
+    }
+
+    fn decide(&mut self, obs: O) -> A {
+        println!("\nstep {}\n{}", self.step, self.console.render(&obs));
+        self.step += 1;
+
+        loop {
+            match self.console.parse(&prompt(self.console.help())) {
+                Ok(action) => return action,
+                Err(e) => println!("{}", e),
+            }
+        }
+    }
+}
+
+/// An agent whose every step is shown; with `pause`, one step per Enter.
+pub struct Watched<'a, T, O, A> {
+    inner: T,
+    console: &'a dyn Console<O, A>,
+    pause: bool,
+    step: u64,
+}
+
+impl<'a, T, O, A> Watched<'a, T, O, A> {
+    pub fn new(inner: T, console: &'a dyn Console<O, A>, pause: bool) -> Self {
+        Self { inner, console, pause, step: 0 }
+    }
+}
+
+impl<T: Agent<O, A>, O, A> Agent<O, A> for Watched<'_, T, O, A> {
+    fn id(&self) -> String {
+        self.inner.id()
+    }
+
+    fn decide(&mut self, obs: O) -> A {
+        println!("\nstep {}\n{}", self.step, self.console.render(&obs));
+        self.step += 1;
+
+        let action = self.inner.decide(obs);
+        println!("{} plays {}", self.inner.id(), self.console.show(&action));
+
+        if self.pause && prompt("enter: next step, c: run to the end, q: quit") == "c" {
+            self.pause = false;
+        }
+        action
+    }
+}
+
+impl<T: Metered, O, A> Metered for Watched<'_, T, O, A> {
+    fn guest(&mut self) -> &mut Guest {
+        self.inner.guest()
+    }
+}
+
+/// One decision as shown at the terminal.
+pub struct Frame {
+    pub board: String,
+    pub action: String,
+    /// `Console::position` before the move.
+    pub position: Option<String>,
+}
+
+/// An agent whose every decision is kept as a `Frame`, for `nets replay`.
+pub struct Recorder<'a, T, O, A> {
+    inner: T,
+    console: &'a dyn Console<O, A>,
+    pub frames: Vec<Frame>,
+}
+
+impl<'a, T, O, A> Recorder<'a, T, O, A> {
+    pub fn new(inner: T, console: &'a dyn Console<O, A>) -> Self {
+        Self { inner, console, frames: Vec::new() }
+    }
+}
+
+impl<T: Agent<O, A>, O, A> Agent<O, A> for Recorder<'_, T, O, A> {
+    fn id(&self) -> String {
+        self.inner.id()
+    }
+
+    fn decide(&mut self, obs: O) -> A {
+        let board = self.console.render(&obs);
+        let position = self.console.position(&obs);
+        let action = self.inner.decide(obs);
+        self.frames.push(Frame { board, action: self.console.show(&action), position });
+        action
+    }
+}
+
+impl<T: Metered, O, A> Metered for Recorder<'_, T, O, A> {
+    fn guest(&mut self) -> &mut Guest {
+        self.inner.guest()
+    }
+}
@@ -0,0 +1,234 @@
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+use serde::{Serialize, Deserialize};
+
+use nets::wasm_agent::{WasmAgent, WasmError, WasmModule};
+
+/* ------------------------------
+   Resource limits
+-------------------------------*/
+
+/// Budgets for one guest agent, `[limits]` in nets.toml.
+///
+/// Fuel is counted per executed wasm instruction, so running out happens
+/// at the same step on every machine and replays reproduce it exactly.
+/// The limits are recorded in each commitment for that reason.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Limits {
+    /// Fuel a single `decide` call may burn.
+    pub fuel_per_decision: u64,
+    /// Fuel all `decide` calls of one match may burn together.
+    pub fuel_per_match: u64,
+    /// Linear memory cap, in 64 KiB pages.
+    pub memory_pages: u32,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            fuel_per_decision: 10_000_000,
+            fuel_per_match: 1_000_000_000,
+            memory_pages: 256,
+        }
+    }
+}
+
+impl Limits {
+    /// No fuel metering and the full wasm32 address space: what guests ran
+    /// under before limits existed, so commitments and traces without a
+    /// recorded `limits` replay the way they were played.
+    pub fn unmetered() -> Self {
+        Self {
+            fuel_per_decision: u64::MAX,
+            fuel_per_match: u64::MAX,
+            memory_pages: 65_536,
+        }
+    }
+}
+
+/// Why a guest stopped deciding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Fault {
+    DecisionFuel,
+    MatchFuel,
+    Trap,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            Fault::DecisionFuel => "out of fuel for one decision",
+            Fault::MatchFuel => "out of fuel for the match",
+            Fault::Trap => "trapped",
+        };
+        f.write_str(reason)
+    }
+}
+
+/* ------------------------------
+   Metered guest
+-------------------------------*/
+
+/// An agent's wasm, compiled once per run and instantiated afresh for
+/// every match.
+pub struct Module(WasmModule);
+
+impl Module {
+    pub fn compile(wasm: &[u8]) -> Result<Self, String> {
+        WasmModule::compile(wasm)
+            .map(Module)
+            .map_err(|e| format!("failed to compile wasm agent: {}", e))
+    }
+}
+
+/// Implemented by every adapter so the plugin can see its guest's state.
+pub trait Metered {
+    fn guest(&mut self) -> &mut Guest;
+}
+
+/// Exported static a guest receives byte observations in (guest ABI 2).
+///
+/// Systems whose observation does not fit the `u64` argument of `decide`
+/// write it into this buffer and pass its length instead.
+pub const OBS_EXPORT: &str = "NETS_OBS";
+
+/// Exported static holding the size of `NETS_OBS` in bytes, as a `u32`.
+pub const OBS_LEN_EXPORT: &str = "NETS_OBS_LEN";
+
+/// Bytes a guest's `NETS_OBS` buffer must hold.
+pub const OBS_LEN: usize = 256;
+
+/// How observations are passed to guests, recorded in every commitment
+/// and trace file so a replay feeds a guest what it was played with.
+///
+/// - 0: chess and rps guests are called with `decide(0)`
+/// - 1: chess passes the position as FEN in `NETS_OBS`
+/// - 2: rps also packs the round and opponent history, see `rps::encode`
+pub const OBS_ENCODING: u32 = 2;
+
+/// Where a guest's `NETS_OBS` buffer is, and how large it says it is.
+struct ObsBuffer {
+    at: u32,
+    len: u32,
+}
+
+/// A `WasmAgent` run under `Limits`, shared by every adapter.
+///
+/// The first fault forfeits the rest of the match: `decide` returns `None`
+/// from then on and the adapter plays its system's default move, which is
+/// what ends up in the trace.
+pub struct Guest {
+    inner: WasmAgent,
+    limits: Limits,
+    fuel_used: u64,
+    fault: Option<Fault>,
+    /// Set by `require_obs_buffer`.
+    obs_buffer: Option<ObsBuffer>,
+}
+
+impl Guest {
+    pub fn load(agent_id: String, module: &Module, limits: &Limits) -> Result<Self, String> {
+        let inner = WasmAgent::instantiate(agent_id, &module.0, limits.memory_pages)
+            .map_err(|e| format!("failed to load wasm agent: {}", e))?;
+        Ok(Self { inner, limits: *limits, fuel_used: 0, fault: None, obs_buffer: None })
+    }
+
+    /// Fail unless the guest exports a `NETS_OBS` buffer of at least
+    /// `OBS_LEN` bytes, for systems that pass observations through it.
+    pub fn require_obs_buffer(mut self, system: &str) -> Result<Self, String> {
+        let missing = || {
+            format!(
+                "{} agents must export {} and {} (guest ABI 2)",
+                system, OBS_EXPORT, OBS_LEN_EXPORT
+            )
+        };
+        let at = self.inner.global_u32(OBS_EXPORT).ok_or_else(missing)?;
+        let len_at = self.inner.global_u32(OBS_LEN_EXPORT).ok_or_else(missing)?;
+
+        let len = self
+            .inner
+            .read_memory(len_at, 4)
+            .ok()
+            .and_then(|b| b.try_into().ok())
+            .map(u32::from_le_bytes)
+            .ok_or_else(|| format!("cannot read {}", OBS_LEN_EXPORT))?;
+        if (len as usize) < OBS_LEN {
+            return Err(format!(
+                "{} holds {} bytes; {} agents need at least {}",
+                OBS_EXPORT, len, system, OBS_LEN
+            ));
+        }
+
+        self.obs_buffer = Some(ObsBuffer { at, len });
+        Ok(self)
+    }
+
+    pub fn id(&self) -> String {
+        self.inner.id.clone()
+    }
+
+    pub fn fault(&self) -> Option<Fault> {
+        self.fault
+    }
+
+    /// `decide` with `obs` in the guest's `NETS_OBS` buffer and its length
+    /// as the argument. An observation larger than the buffer, or a write
+    /// the guest's memory cannot take, is a trap.
+    pub fn decide_bytes(&mut self, obs: &[u8]) -> Option<u64> {
+        if self.fault.is_some() {
+            return None;
+        }
+
+        let written = match &self.obs_buffer {
+            Some(buf) if obs.len() <= buf.len as usize => {
+                self.inner.write_memory(buf.at, obs).is_ok()
+            }
+            _ => false,
+        };
+        if !written {
+            self.fault = Some(Fault::Trap);
+            return None;
+        }
+        self.decide(obs.len() as u64)
+    }
+
+    pub fn decide(&mut self, input: u64) -> Option<u64> {
+        if self.fault.is_some() {
+            return None;
+        }
+
+        let left = self.limits.fuel_per_match.saturating_sub(self.fuel_used);
+        let budget = self.limits.fuel_per_decision.min(left);
+
+        // A panicking guest counts as a trap of this agent only, so the
+        // other agents at a shared table keep playing.
+        let decided =
+            panic::catch_unwind(AssertUnwindSafe(|| self.inner.decide_with_fuel(input, budget)));
+        let Ok(decided) = decided else {
+            self.fault = Some(Fault::Trap);
+            return None;
+        };
+
+        match decided {
+            Ok((output, burned)) => {
+                self.fuel_used += burned;
+                Some(output)
+            }
+            Err(WasmError::OutOfFuel) if budget < self.limits.fuel_per_decision => {
+                self.fault = Some(Fault::MatchFuel);
+                None
+            }
+            Err(WasmError::OutOfFuel) => {
+                self.fault = Some(Fault::DecisionFuel);
+                None
+            }
+            Err(_) => {
+                self.fault = Some(Fault::Trap);
+                None
+            }
+        }
+    }
+}
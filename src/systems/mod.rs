@@ -0,0 +1,740 @@
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use nets::{
+    agent::Agent,
+    system::System,
+    league::League,
+    league_runner::{run_league_with_traces, LeagueConfig},
+    merkle::MerkleTree,
+    ledger::Ledger,
+    match_trace::{run_match_with_trace, MatchTrace, TraceStep},
+};
+
+pub mod snake;
+pub mod snake_arena;
+pub mod arena;
+pub mod chess;
+pub mod rps;
+
+pub mod console;
+pub mod guest;
+pub mod parallel;
+
+use console::{Console, Frame, Human, Recorder, Watched};
+use guest::{Fault, Limits, Metered, Module, OBS_ENCODING};
+
+/* ------------------------------
+   Per-game registration
+-------------------------------*/
+
+/// Everything a game has to provide to be usable from the CLI.
+///
+/// A new game is one module implementing this trait plus one line in
+/// `PLUGINS`; every command dispatches through the registry.
+pub trait GameSystem: 'static {
+    const NAME: &'static str;
+    /// Oldest guest ABI the adapter can play; see `discovery`.
+    const MIN_ABI: u32 = 1;
+
+    type Params: Serialize + DeserializeOwned + Default + Sync;
+    type Sys: Clone + Sync;
+    /// What an agent is shown each step, and what it answers.
+    type Obs;
+    type Act;
+    type Adapter: Agent<Self::Obs, Self::Act> + Metered;
+
+    /// Reject parameter combinations the system cannot run with.
+    fn validate(_params: &Self::Params) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Agents sharing one instance; single-agent systems seat one.
+    fn seats(_params: &Self::Params) -> usize {
+        1
+    }
+
+    fn build(params: &Self::Params, seed: u64) -> Self::Sys;
+
+    /// Load a guest that is passed observations as `encoding` says; new
+    /// matches use `OBS_ENCODING`, replays the one they were played with.
+    fn load_agent(
+        agent_id: String,
+        module: &Module,
+        limits: &Limits,
+        encoding: u32,
+    ) -> Result<Self::Adapter, String>;
+
+    /// Play one scored match with every agent of `table`, returning each
+    /// seat's `(score, matches counted by the league, trace)`. Systems
+    /// whose rules are in nets-core use `play_solo`.
+    fn play<A>(
+        system: Self::Sys,
+        table: &mut [A],
+        ledger: &Ledger,
+        league: &League,
+    ) -> Vec<(i64, usize, MatchTrace)>
+    where
+        A: Agent<Self::Obs, Self::Act>;
+
+    /// The per-seat traces a verifier recomputes for `table`. Generic over
+    /// the agent so `nets replay` can record the seats as they play.
+    fn replay<A>(system: Self::Sys, table: &mut [A]) -> Vec<MatchTrace>
+    where
+        A: Agent<Self::Obs, Self::Act>;
+
+    /// Terminal view for `nets play`; `None` if the system has none.
+    fn console(_params: &Self::Params) -> Option<Box<dyn Console<Obs<Self>, Act<Self>>>> {
+        None
+    }
+}
+
+/// Observation and action types of a game.
+pub type Obs<G> = <G as GameSystem>::Obs;
+pub type Act<G> = <G as GameSystem>::Act;
+
+/// `GameSystem::play` for single-agent nets-core systems: each agent on
+/// its own copy of `system`.
+pub fn play_solo<S, A>(
+    system: S,
+    table: &mut [A],
+    ledger: &Ledger,
+    league: &League,
+) -> Vec<(i64, usize, MatchTrace)>
+where
+    S: System + Clone,
+    A: Agent<S::Observation, S::Action>,
+{
+    let single = LeagueConfig { matches_per_agent: 1 };
+    table
+        .iter_mut()
+        .map(|agent| {
+            let (results, mut traces) = run_league_with_traces(
+                system.clone(),
+                std::slice::from_mut(agent),
+                ledger,
+                league,
+                &single,
+            );
+            let trace = traces.remove(0).remove(0);
+            (results[0].total_score as i64, results[0].matches.len(), trace)
+        })
+        .collect()
+}
+
+/// `GameSystem::replay` for single-agent nets-core systems.
+pub fn replay_solo<S, A>(system: S, table: &mut [A]) -> Vec<MatchTrace>
+where
+    S: System + Clone,
+    A: Agent<S::Observation, S::Action>,
+{
+    table
+        .iter_mut()
+        .map(|agent| run_match_with_trace(system.clone(), agent))
+        .collect()
+}
+
+/* ------------------------------
+   Type-erased plugin
+-------------------------------*/
+
+/// Per-agent league result, independent of the system type.
+pub struct Standing {
+    pub agent_id: String,
+    pub total_score: i64,
+    pub matches: usize,
+}
+
+/// How a league is played; shared by every match in it.
+pub struct LeagueSpec<'a> {
+    pub params: &'a Value,
+    pub league: &'a League,
+    pub matches: usize,
+    /// Run seed; match `k` is played with `match_seed(seed, k)`.
+    pub seed: u64,
+    /// Worker threads; results do not depend on this.
+    pub jobs: usize,
+    pub limits: Limits,
+}
+
+pub struct LeagueOutcome {
+    /// Agents that finished; disqualified agents have no standing.
+    pub standings: Vec<Standing>,
+    pub commitments: Vec<AgentTraces>,
+    pub disqualified: Vec<Disqualified>,
+}
+
+/// Roots of the matches that scored one agent, in the order played.
+pub struct AgentTraces {
+    pub agent: String,
+    pub matches: Vec<PlayedMatch>,
+}
+
+/// One match an agent was scored on.
+pub struct PlayedMatch {
+    pub seed: u64,
+    pub root: [u8; 32],
+    /// The agent's trace steps, committed so a fraud proof can later show
+    /// which committed step a replay disagrees with.
+    pub steps: Vec<TraceStep>,
+    /// Every agent at the table in seat order; empty when it played alone.
+    pub lineup: Vec<String>,
+}
+
+impl AgentTraces {
+    /// The agent's committed root: the Merkle root over its match roots.
+    pub fn root(&self) -> [u8; 32] {
+        let roots: Vec<[u8; 32]> = self.matches.iter().map(|m| m.root).collect();
+        aggregate_root(&roots)
+    }
+}
+
+/// A `nets play` session: the seeded instance, and who plays it.
+pub struct Session {
+    pub seed: u64,
+    /// Play the instance yourself before the agent does.
+    pub human: bool,
+    /// Wait for Enter between the agent's steps.
+    pub pause: bool,
+}
+
+/// Scores of a `nets play` session.
+pub struct SessionScores {
+    pub human: Option<i64>,
+    pub agent: i64,
+}
+
+/// Per-seat result of one match: score and trace, or why that agent is
+/// disqualified.
+pub type SeatResult = Result<(i64, MatchTrace), String>;
+
+/// An agent removed from a run because it failed to load or trapped.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Disqualified {
+    pub agent: String,
+    pub reason: String,
+}
+
+pub trait SystemPlugin: Sync {
+    fn name(&self) -> &'static str;
+    fn default_params(&self) -> Value;
+
+    /// Oldest guest ABI this system's agents may be built against.
+    fn min_abi(&self) -> u32;
+
+    /// Agents sharing one instance under `params`.
+    fn seats(&self, params: &Value) -> usize;
+
+    /// Layer `file` and then `overrides` onto the defaults, validate,
+    /// and return the normalized params to record in commitments.
+    fn resolve_params(
+        &self,
+        file: Option<&Value>,
+        overrides: &[(String, String)],
+    ) -> Result<Value, String>;
+
+    /// Play `agents`, each compiled once, over every match of `spec`.
+    fn run_league(
+        &self,
+        spec: &LeagueSpec,
+        agents: &[(String, Module)],
+        ledger: &Ledger,
+    ) -> LeagueOutcome;
+
+    /// Play one scored match with `lineup` from fresh instances, returning
+    /// each seat's score and the trace of that same match. Single-agent
+    /// systems play every seat on its own copy of the instance. A seat that
+    /// traps at a shared table is dropped and the rest play again, so the
+    /// `Ok` seats are the table the returned traces were played at.
+    fn play(
+        &self,
+        params: &Value,
+        seed: u64,
+        lineup: &[(&str, &Module)],
+        ledger: &Ledger,
+        league: &League,
+        limits: &Limits,
+    ) -> Vec<SeatResult>;
+
+    /// Play `session` at the terminal: the person, then the agent, on the
+    /// same instance, with every step shown.
+    fn play_interactive(
+        &self,
+        params: &Value,
+        agent: (&str, &[u8]),
+        ledger: &Ledger,
+        league: &League,
+        limits: &Limits,
+        session: &Session,
+    ) -> Result<SessionScores, String>;
+
+    /// Replay one match with `lineup` keeping every frame of `seat`, for
+    /// `nets replay`; frame `i` belongs to step `i` of the returned trace.
+    /// `encoding` is the observation encoding the match was played with.
+    fn record(
+        &self,
+        params: &Value,
+        seed: u64,
+        lineup: &[(&str, &[u8])],
+        seat: usize,
+        limits: &Limits,
+        encoding: u32,
+    ) -> Result<(Vec<Frame>, MatchTrace), String>;
+
+    /// The trace of `seat` a verifier recomputes; fails only if an agent
+    /// cannot be loaded, since a trap is recorded in the trace as a forfeit.
+    fn replay(
+        &self,
+        params: &Value,
+        seed: u64,
+        lineup: &[(&str, &[u8])],
+        seat: usize,
+        limits: &Limits,
+        encoding: u32,
+    ) -> Result<MatchTrace, String>;
+}
+
+struct Registered<G>(PhantomData<fn() -> G>);
+
+impl<G: GameSystem> Registered<G> {
+    fn params(&self, params: &Value) -> G::Params {
+        serde_json::from_value(params.clone()).unwrap_or_else(|e| {
+            eprintln!("invalid {} params: {}", G::NAME, e);
+            std::process::exit(1);
+        })
+    }
+}
+
+impl<G: GameSystem> SystemPlugin for Registered<G> {
+    fn name(&self) -> &'static str {
+        G::NAME
+    }
+
+    fn default_params(&self) -> Value {
+        serde_json::to_value(G::Params::default()).unwrap()
+    }
+
+    fn min_abi(&self) -> u32 {
+        G::MIN_ABI
+    }
+
+    fn seats(&self, params: &Value) -> usize {
+        G::seats(&self.params(params))
+    }
+
+    fn resolve_params(
+        &self,
+        file: Option<&Value>,
+        overrides: &[(String, String)],
+    ) -> Result<Value, String> {
+        let mut merged = self.default_params();
+
+        if let Some(Value::Object(fields)) = file {
+            for (k, v) in fields {
+                merged[k] = v.clone();
+            }
+        } else if file.is_some() {
+            return Err("params file must contain a JSON object".into());
+        }
+
+        for (k, v) in overrides {
+            merged[k] = serde_json::from_str(v).unwrap_or_else(|_| Value::String(v.clone()));
+        }
+
+        let params: G::Params = serde_json::from_value(merged)
+            .map_err(|e| format!("invalid {} params: {}", G::NAME, e))?;
+        G::validate(&params)
+            .map_err(|e| format!("invalid {} params: {}", G::NAME, e))?;
+
+        Ok(serde_json::to_value(params).unwrap())
+    }
+
+    fn run_league(
+        &self,
+        spec: &LeagueSpec,
+        agents: &[(String, Module)],
+        ledger: &Ledger,
+    ) -> LeagueOutcome {
+        let params = self.params(spec.params);
+        let seats = G::seats(&params).max(1);
+
+        // Every match starts from fresh instances, so each table is an
+        // independent unit of work on any thread and any one match can be
+        // replayed and proven on its own. Single-agent systems seat every
+        // agent alone on the same seeded instance, keeping scores
+        // comparable; shared boards are drawn per match from its seed.
+        let tables: Vec<(u64, Vec<usize>)> = (0..spec.matches as u64)
+            .flat_map(|k| {
+                let seed = match_seed(spec.seed, k);
+                seating(agents.len(), seats, seed, k)
+                    .into_iter()
+                    .map(move |table| (seed, table))
+            })
+            .collect();
+
+        let played = parallel::map(&tables, spec.jobs, |(seed, table)| {
+            let lineup: Vec<(&str, &Module)> =
+                table.iter().map(|&i| (agents[i].0.as_str(), &agents[i].1)).collect();
+            play_table::<G>(&params, *seed, &lineup, ledger, spec.league, &spec.limits)
+        });
+
+        let mut standings: Vec<Standing> = agents
+            .iter()
+            .map(|(id, _)| Standing { agent_id: id.clone(), total_score: 0, matches: 0 })
+            .collect();
+        let mut traces: Vec<AgentTraces> = agents
+            .iter()
+            .map(|(id, _)| AgentTraces { agent: id.clone(), matches: Vec::new() })
+            .collect();
+        let mut faults: Vec<Option<String>> = vec![None; agents.len()];
+
+        for ((seed, table), results) in tables.iter().zip(played) {
+            // Seats that failed to load or trapped did not play the match
+            // the others are scored on, so they are not in its lineup.
+            let lineup: Vec<String> = if table.len() > 1 {
+                table
+                    .iter()
+                    .zip(&results)
+                    .filter(|(_, r)| r.is_ok())
+                    .map(|(&i, _)| agents[i].0.clone())
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            for (&i, result) in table.iter().zip(results) {
+                match result {
+                    Ok((score, matches, trace)) => {
+                        standings[i].total_score += score;
+                        standings[i].matches += matches;
+                        traces[i].matches.push(PlayedMatch {
+                            seed: *seed,
+                            root: trace.merkle.root(),
+                            steps: trace.steps,
+                            lineup: lineup.clone(),
+                        });
+                    }
+                    Err(reason) => {
+                        faults[i].get_or_insert(reason);
+                    }
+                }
+            }
+        }
+
+        let mut outcome = LeagueOutcome {
+            standings: Vec::new(),
+            commitments: Vec::new(),
+            disqualified: Vec::new(),
+        };
+        for ((standing, traces), fault) in standings.into_iter().zip(traces).zip(faults) {
+            match fault {
+                None => {
+                    outcome.standings.push(standing);
+                    outcome.commitments.push(traces);
+                }
+                Some(reason) => {
+                    outcome.disqualified.push(Disqualified { agent: traces.agent, reason });
+                }
+            }
+        }
+        outcome
+    }
+
+    fn play(
+        &self,
+        params: &Value,
+        seed: u64,
+        lineup: &[(&str, &Module)],
+        ledger: &Ledger,
+        league: &League,
+        limits: &Limits,
+    ) -> Vec<SeatResult> {
+        play_table::<G>(&self.params(params), seed, lineup, ledger, league, limits)
+            .into_iter()
+            .map(|r| r.map(|(score, _, trace)| (score, trace)))
+            .collect()
+    }
+
+    fn play_interactive(
+        &self,
+        params: &Value,
+        (agent_id, wasm): (&str, &[u8]),
+        ledger: &Ledger,
+        league: &League,
+        limits: &Limits,
+        session: &Session,
+    ) -> Result<SessionScores, String> {
+        let params = self.params(params);
+        let console = G::console(&params)
+            .filter(|_| G::seats(&params) == 1)
+            .ok_or_else(|| format!("{} cannot be played in the terminal", G::NAME))?;
+
+        let human = session.human.then(|| {
+            println!("your game, seed {} ({}, q to quit)", session.seed, console.help());
+            let mut you = vec![Human::new(console.as_ref())];
+            G::play(G::build(&params, session.seed), &mut you, ledger, league)[0].0
+        });
+
+        println!("\n{}'s game, seed {}", agent_id, session.seed);
+        let module = Module::compile(wasm)?;
+        let agent = G::load_agent(agent_id.to_string(), &module, limits, OBS_ENCODING)?;
+        let mut watched = vec![Watched::new(agent, console.as_ref(), session.pause)];
+        let (agent, _, _) =
+            G::play(G::build(&params, session.seed), &mut watched, ledger, league).remove(0);
+        check_trap(&mut watched[0])?;
+
+        Ok(SessionScores { human, agent })
+    }
+
+    fn record(
+        &self,
+        params: &Value,
+        seed: u64,
+        lineup: &[(&str, &[u8])],
+        seat: usize,
+        limits: &Limits,
+        encoding: u32,
+    ) -> Result<(Vec<Frame>, MatchTrace), String> {
+        let params = self.params(params);
+        let console = G::console(&params)
+            .ok_or_else(|| format!("{} cannot be shown in the terminal", G::NAME))?;
+
+        isolate(|| {
+            let mut table = lineup
+                .iter()
+                .map(|(id, wasm)| {
+                    let module = Module::compile(wasm)?;
+                    let agent = G::load_agent(id.to_string(), &module, limits, encoding)?;
+                    Ok(Recorder::new(agent, console.as_ref()))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let trace = G::replay(G::build(&params, seed), &mut table)
+                .into_iter()
+                .nth(seat)
+                .ok_or_else(|| format!("no seat {} in a table of {}", seat, lineup.len()))?;
+            Ok((table.swap_remove(seat).frames, trace))
+        })
+    }
+
+    fn replay(
+        &self,
+        params: &Value,
+        seed: u64,
+        lineup: &[(&str, &[u8])],
+        seat: usize,
+        limits: &Limits,
+        encoding: u32,
+    ) -> Result<MatchTrace, String> {
+        let system = G::build(&self.params(params), seed);
+        isolate(|| {
+            let mut table = lineup
+                .iter()
+                .map(|(id, wasm)| {
+                    let module = Module::compile(wasm)?;
+                    G::load_agent(id.to_string(), &module, limits, encoding)
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            G::replay(system, &mut table)
+                .into_iter()
+                .nth(seat)
+                .ok_or_else(|| format!("no seat {} in a table of {}", seat, lineup.len()))
+        })
+    }
+}
+
+/// One scored match with `lineup` at one table: `(score, matches counted
+/// by the league, trace)` per seat. An agent that fails to load sits out.
+/// One that traps is reported; at a shared table it is also removed and
+/// the match is played again without it, so it cannot shape the other
+/// seats' game. The seats that come back `Ok` are the table as played.
+fn play_table<G: GameSystem>(
+    params: &G::Params,
+    seed: u64,
+    lineup: &[(&str, &Module)],
+    ledger: &Ledger,
+    league: &League,
+    limits: &Limits,
+) -> Vec<Result<(i64, usize, MatchTrace), String>> {
+    let shared = G::seats(params) > 1;
+    let mut out: Vec<Result<(i64, usize, MatchTrace), String>> =
+        lineup.iter().map(|_| Err("not played".into())).collect();
+    let mut seated: Vec<usize> = (0..lineup.len()).collect();
+
+    loop {
+        let mut table: Vec<G::Adapter> = Vec::new();
+        seated.retain(|&i| {
+            let (id, module) = lineup[i];
+            match isolate(|| G::load_agent(id.to_string(), module, limits, OBS_ENCODING)) {
+                Ok(agent) => {
+                    table.push(agent);
+                    true
+                }
+                Err(reason) => {
+                    out[i] = Err(reason);
+                    false
+                }
+            }
+        });
+        if table.is_empty() {
+            return out;
+        }
+
+        let played = isolate(|| Ok(G::play(G::build(params, seed), &mut table, ledger, league)));
+        let results = match played {
+            Ok(results) => results,
+            Err(reason) => {
+                for &i in &seated {
+                    out[i] = Err(reason.clone());
+                }
+                return out;
+            }
+        };
+
+        let mut trapped = false;
+        for ((&i, agent), result) in seated.iter().zip(&mut table).zip(results) {
+            out[i] = check_trap(agent).map(|_| result);
+            trapped |= out[i].is_err();
+        }
+        if !shared || !trapped {
+            return out;
+        }
+        seated.retain(|&i| out[i].is_ok());
+    }
+}
+
+/// Agent indices split into the tables of match `round`. Single-seat
+/// systems keep run order. Shared tables are shuffled with `seed` and
+/// balanced so no table is more than one seat short of another. When
+/// that would still leave an agent alone at a shared table, where it
+/// has no opponents to be compared against, it sits the match out
+/// instead; the bye rotates through the agents in run order.
+fn seating(agents: usize, seats: usize, seed: u64, round: u64) -> Vec<Vec<usize>> {
+    let mut order: Vec<usize> = (0..agents).collect();
+    if seats <= 1 || agents == 0 {
+        return order.into_iter().map(|i| vec![i]).collect();
+    }
+
+    if agents > 1 && agents / agents.div_ceil(seats) < 2 {
+        order.remove((round % agents as u64) as usize);
+    }
+    for i in (1..order.len()).rev() {
+        let j = (match_seed(seed, i as u64) % (i as u64 + 1)) as usize;
+        order.swap(i, j);
+    }
+
+    let tables = order.len().div_ceil(seats);
+    let (size, extra) = (order.len() / tables, order.len() % tables);
+    let mut rest = order.as_slice();
+    (0..tables)
+        .map(|t| {
+            let (table, tail) = rest.split_at(size + usize::from(t < extra));
+            rest = tail;
+            table.to_vec()
+        })
+        .collect()
+}
+
+/* ------------------------------
+   Fault isolation
+-------------------------------*/
+
+/// Run one agent's work, turning a panic into an error so a broken agent
+/// cannot take the rest of the run down with it.
+fn isolate<T>(f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| Err("panicked".into()))
+}
+
+fn check_trap(agent: &mut impl Metered) -> Result<(), String> {
+    match agent.guest().fault() {
+        Some(Fault::Trap) => Err(Fault::Trap.to_string()),
+        _ => Ok(()),
+    }
+}
+
+/* ------------------------------
+   Seeds
+-------------------------------*/
+
+/// Seed for match `index` of a run seeded with `run_seed` (splitmix64).
+pub fn match_seed(run_seed: u64, index: u64) -> u64 {
+    let mut z = run_seed.wrapping_add(index.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Merkle root over match roots, in the order the matches were played.
+/// This is the root an agent commits to for a run.
+pub fn aggregate_root(roots: &[[u8; 32]]) -> [u8; 32] {
+    MerkleTree::from_leaves(roots.to_vec()).root()
+}
+
+/* ------------------------------
+   Registry
+-------------------------------*/
+
+const PLUGINS: &[&dyn SystemPlugin] = &[
+    &Registered::<snake::Snake>(PhantomData),
+    &Registered::<snake_arena::SnakeArena>(PhantomData),
+    &Registered::<chess::Chess>(PhantomData),
+    &Registered::<rps::Rps>(PhantomData),
+];
+
+pub fn lookup(name: &str) -> Option<&'static dyn SystemPlugin> {
+    PLUGINS.iter().copied().find(|p| p.name() == name)
+}
+
+/// Names of every registered system, in registry order.
+pub fn names() -> Vec<&'static str> {
+    PLUGINS.iter().map(|p| p.name()).collect()
+}
+
+/// Resolve a system name or exit with the list of known systems.
+pub fn resolve(name: &str) -> &'static dyn SystemPlugin {
+    lookup(name).unwrap_or_else(|| {
+        eprintln!("unknown system '{}' (known: {})", name, names().join(", "));
+        std::process::exit(1);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sizes(agents: usize, seats: usize) -> Vec<usize> {
+        seating(agents, seats, 7, 0).iter().map(Vec::len).collect()
+    }
+
+    #[test]
+    fn shared_tables_are_balanced() {
+        assert_eq!(sizes(5, 4), vec![3, 2]);
+        assert_eq!(sizes(8, 4), vec![4, 4]);
+        assert_eq!(sizes(9, 4), vec![3, 3, 3]);
+        assert_eq!(sizes(7, 3), vec![3, 2, 2]);
+
+        let mut seated: Vec<usize> = seating(9, 4, 7, 0).concat();
+        seated.sort();
+        assert_eq!(seated, (0..9).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn a_lone_agent_takes_a_rotating_bye() {
+        for round in 0..6 {
+            let tables = seating(3, 2, 11, round);
+            assert_eq!(tables.len(), 1);
+            assert_eq!(tables[0].len(), 2);
+            assert!(!tables[0].contains(&(round as usize % 3)));
+        }
+        assert_eq!(sizes(5, 2), vec![2, 2]);
+    }
+
+    #[test]
+    fn single_seat_systems_keep_run_order() {
+        assert_eq!(seating(3, 1, 5, 0), vec![vec![0], vec![1], vec![2]]);
+        assert_eq!(seating(1, 4, 5, 0), vec![vec![0]]);
+        assert!(seating(0, 4, 5, 0).is_empty());
+    }
+}
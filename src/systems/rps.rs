@@ -0,0 +1,231 @@
+use serde::{Serialize, Deserialize};
+
+use nets::{
+    ledger::Ledger,
+    league::League,
+    match_trace::MatchTrace,
+    agent::Agent,
+    rps::RpsSystem,
+    rps::system::RpsObservation,
+    rps::r#move::RpsMove,
+};
+
+use super::console::Console;
+use super::guest::{Guest, Limits, Metered, Module};
+
+pub struct RpsWasmAgent {
+    inner: Guest,
+    /// Observation encoding the agent is played with; see `OBS_ENCODING`.
+    encoding: u32,
+}
+
+impl RpsWasmAgent {
+    pub fn load(
+        agent_id: String,
+        module: &Module,
+        limits: &Limits,
+        encoding: u32,
+    ) -> Result<Self, String> {
+        Ok(Self { inner: Guest::load(agent_id, module, limits)?, encoding })
+    }
+}
+
+impl Metered for RpsWasmAgent {
+    fn guest(&mut self) -> &mut Guest {
+        &mut self.inner
+    }
+}
+
+/// Most recent opponent moves a guest is told about.
+pub const HISTORY: usize = 20;
+
+fn move_code(m: &RpsMove) -> u64 {
+    match m {
+        RpsMove::Rock => 0,
+        RpsMove::Paper => 1,
+        RpsMove::Scissors => 2,
+    }
+}
+
+/// Pack an RPS observation into the guest's `u64` input.
+///
+/// | bits  | value                                                |
+/// |-------|------------------------------------------------------|
+/// | 0-15  | round, saturating at 65535                           |
+/// | 16-20 | number of opponent moves present (at most 20)        |
+/// | 21-60 | opponent moves, 2 bits each, most recent first       |
+///
+/// Moves are 0 rock, 1 paper, 2 scissors; unused slots are zero.
+pub fn encode(obs: &RpsObservation) -> u64 {
+    pack(obs.round as u64, &obs.opponent_history)
+}
+
+/// `encode` from the round and the opponent's moves, oldest first.
+fn pack(round: u64, history: &[RpsMove]) -> u64 {
+    let recent: Vec<&RpsMove> = history.iter().rev().take(HISTORY).collect();
+
+    let mut packed = round.min(0xFFFF) | ((recent.len() as u64) << 16);
+    for (i, m) in recent.iter().enumerate() {
+        packed |= move_code(m) << (21 + 2 * i);
+    }
+    packed
+}
+
+impl Agent<RpsObservation, RpsMove> for RpsWasmAgent {
+    fn id(&self) -> String {
+        self.inner.id()
+    }
+
+    /// Under encodings before 2 the guest is told nothing.
+    fn decide(&mut self, obs: RpsObservation) -> RpsMove {
+        let input = if self.encoding >= 2 { encode(&obs) } else { 0 };
+        // A forfeited agent, like an invalid output, plays rock.
+        self.inner
+            .decide(input)
+            .and_then(RpsMove::from_u64)
+            .unwrap_or(RpsMove::Rock)
+    }
+}
+
+/* ------------------------------
+   Terminal view
+-------------------------------*/
+
+fn move_name(m: &RpsMove) -> &'static str {
+    match m {
+        RpsMove::Rock => "rock",
+        RpsMove::Paper => "paper",
+        RpsMove::Scissors => "scissors",
+    }
+}
+
+/// The round and the opponent's last move.
+pub struct RpsConsole;
+
+impl Console<RpsObservation, RpsMove> for RpsConsole {
+    fn render(&self, obs: &RpsObservation) -> String {
+        let last = obs.opponent_history.last().map(move_name).unwrap_or("-");
+        format!("round {}, opponent's last move: {}", obs.round, last)
+    }
+
+    fn parse(&self, input: &str) -> Result<RpsMove, String> {
+        match input {
+            "r" | "rock" => Ok(RpsMove::Rock),
+            "p" | "paper" => Ok(RpsMove::Paper),
+            "s" | "scissors" => Ok(RpsMove::Scissors),
+            _ => Err(format!("unknown move '{}'", input)),
+        }
+    }
+
+    fn show(&self, m: &RpsMove) -> String {
+        move_name(m).into()
+    }
+
+    fn help(&self) -> &'static str {
+        "r/p/s"
+    }
+}
+
+/* ------------------------------
+   Registration
+-------------------------------*/
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RpsParams {
+    pub rounds: u64,
+}
+
+impl Default for RpsParams {
+    fn default() -> Self {
+        Self { rounds: 100 }
+    }
+}
+
+pub struct Rps;
+
+impl super::GameSystem for Rps {
+    const NAME: &'static str = "rps";
+
+    type Params = RpsParams;
+    type Sys = RpsSystem;
+    type Obs = RpsObservation;
+    type Act = RpsMove;
+    type Adapter = RpsWasmAgent;
+
+    fn validate(p: &RpsParams) -> Result<(), String> {
+        if p.rounds == 0 {
+            return Err("rounds must be positive".into());
+        }
+        Ok(())
+    }
+
+    fn build(p: &RpsParams, seed: u64) -> RpsSystem {
+        RpsSystem::new(p.rounds).with_seed(seed)
+    }
+
+    fn load_agent(
+        agent_id: String,
+        module: &Module,
+        limits: &Limits,
+        encoding: u32,
+    ) -> Result<RpsWasmAgent, String> {
+        RpsWasmAgent::load(agent_id, module, limits, encoding)
+    }
+
+    fn play<A>(
+        system: RpsSystem,
+        table: &mut [A],
+        ledger: &Ledger,
+        league: &League,
+    ) -> Vec<(i64, usize, MatchTrace)>
+    where
+        A: Agent<RpsObservation, RpsMove>,
+    {
+        super::play_solo(system, table, ledger, league)
+    }
+
+    fn replay<A>(system: RpsSystem, table: &mut [A]) -> Vec<MatchTrace>
+    where
+        A: Agent<RpsObservation, RpsMove>,
+    {
+        super::replay_solo(system, table)
+    }
+
+    fn console(_params: &RpsParams) -> Option<Box<dyn Console<RpsObservation, RpsMove>>> {
+        Some(Box::new(RpsConsole))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_and_count_fill_the_low_bits() {
+        assert_eq!(pack(0, &[]), 0);
+        assert_eq!(pack(7, &[]), 7);
+        assert_eq!(pack(70_000, &[]), 0xFFFF);
+        assert_eq!(pack(1, &[RpsMove::Rock]), 1 | 1 << 16);
+    }
+
+    #[test]
+    fn moves_are_packed_most_recent_first_from_bit_21() {
+        let history = [RpsMove::Rock, RpsMove::Paper, RpsMove::Scissors];
+        assert_eq!(pack(5, &history), 5 | 3 << 16 | 2 << 21 | 1 << 23);
+    }
+
+    #[test]
+    fn only_the_last_twenty_moves_are_packed() {
+        let history: Vec<RpsMove> = (0..25)
+            .map(|i| if i < 5 { RpsMove::Scissors } else { RpsMove::Paper })
+            .collect();
+
+        let packed = pack(25, &history);
+        assert_eq!(packed >> 16 & 0b11111, HISTORY as u64);
+        for i in 0..HISTORY {
+            assert_eq!(packed >> (21 + 2 * i) & 0b11, 1, "slot {}", i);
+        }
+        assert_eq!(packed >> 61, 0);
+    }
+}
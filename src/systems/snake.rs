@@ -0,0 +1,197 @@
+use serde::{Serialize, Deserialize};
+
+use nets::{
+    ledger::Ledger,
+    league::League,
+    match_trace::MatchTrace,
+    system::System,
+    agent::Agent,
+    snake::{SnakeSystem, Dir},
+};
+
+use super::console::Console;
+use super::guest::{Guest, Limits, Metered, Module};
+
+pub struct SnakeWasmAgent {
+    inner: Guest,
+}
+
+impl SnakeWasmAgent {
+    pub fn load(agent_id: String, module: &Module, limits: &Limits) -> Result<Self, String> {
+        Ok(Self { inner: Guest::load(agent_id, module, limits)? })
+    }
+}
+
+impl Metered for SnakeWasmAgent {
+    fn guest(&mut self) -> &mut Guest {
+        &mut self.inner
+    }
+}
+
+pub(crate) fn u64_to_dir(v: u64) -> Dir {
+    match v % 4 {
+        0 => Dir::Up,
+        1 => Dir::Down,
+        2 => Dir::Left,
+        _ => Dir::Right,
+    }
+}
+
+impl Agent<
+    <SnakeSystem as System>::Observation,
+    <SnakeSystem as System>::Action,
+> for SnakeWasmAgent {
+    fn id(&self) -> String {
+        self.inner.id()
+    }
+
+    fn decide(
+        &mut self,
+        obs: <SnakeSystem as System>::Observation,
+    ) -> <SnakeSystem as System>::Action {
+        let ((hx, hy), (ax, ay), _) = obs;
+
+        let packed =
+            ((hx as u64 & 0xFF) << 24)
+            | ((hy as u64 & 0xFF) << 16)
+            | ((ax as u64 & 0xFF) << 8)
+            | (ay as u64 & 0xFF);
+
+        // A forfeited agent keeps heading up.
+        self.inner.decide(packed).map(u64_to_dir).unwrap_or(Dir::Up)
+    }
+}
+
+/* ------------------------------
+   Terminal view
+-------------------------------*/
+
+/// Board of `width` x `height`, row 0 on top: `H` head, `*` food.
+pub struct SnakeConsole {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Console<<SnakeSystem as System>::Observation, Dir> for SnakeConsole {
+    fn render(&self, obs: &<SnakeSystem as System>::Observation) -> String {
+        let (head, food) = (obs.0, obs.1);
+
+        let mut board = String::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                board.push(if (x, y) == head {
+                    'H'
+                } else if (x, y) == food {
+                    '*'
+                } else {
+                    '.'
+                });
+            }
+            board.push('\n');
+        }
+        board.pop();
+        board
+    }
+
+    fn parse(&self, input: &str) -> Result<Dir, String> {
+        match input {
+            "w" | "up" => Ok(Dir::Up),
+            "s" | "down" => Ok(Dir::Down),
+            "a" | "left" => Ok(Dir::Left),
+            "d" | "right" => Ok(Dir::Right),
+            _ => Err(format!("unknown direction '{}'", input)),
+        }
+    }
+
+    fn show(&self, dir: &Dir) -> String {
+        let name = match dir {
+            Dir::Up => "up",
+            Dir::Down => "down",
+            Dir::Left => "left",
+            Dir::Right => "right",
+        };
+        name.into()
+    }
+
+    fn help(&self) -> &'static str {
+        "w/a/s/d to move"
+    }
+}
+
+/* ------------------------------
+   Registration
+-------------------------------*/
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnakeParams {
+    pub width: i32,
+    pub height: i32,
+    pub steps: u64,
+}
+
+impl Default for SnakeParams {
+    fn default() -> Self {
+        Self { width: 10, height: 10, steps: 300 }
+    }
+}
+
+pub struct Snake;
+
+impl super::GameSystem for Snake {
+    const NAME: &'static str = "snake";
+
+    type Params = SnakeParams;
+    type Sys = SnakeSystem;
+    type Obs = <SnakeSystem as System>::Observation;
+    type Act = Dir;
+    type Adapter = SnakeWasmAgent;
+
+    fn validate(p: &SnakeParams) -> Result<(), String> {
+        if !(4..=255).contains(&p.width) || !(4..=255).contains(&p.height) {
+            return Err("width and height must be between 4 and 255".into());
+        }
+        if p.steps == 0 {
+            return Err("steps must be positive".into());
+        }
+        Ok(())
+    }
+
+    fn build(p: &SnakeParams, seed: u64) -> SnakeSystem {
+        SnakeSystem::new(p.width, p.height, p.steps).with_seed(seed)
+    }
+
+    fn load_agent(
+        agent_id: String,
+        module: &Module,
+        limits: &Limits,
+        _encoding: u32,
+    ) -> Result<SnakeWasmAgent, String> {
+        SnakeWasmAgent::load(agent_id, module, limits)
+    }
+
+    fn play<A>(
+        system: SnakeSystem,
+        table: &mut [A],
+        ledger: &Ledger,
+        league: &League,
+    ) -> Vec<(i64, usize, MatchTrace)>
+    where
+        A: Agent<<SnakeSystem as System>::Observation, Dir>,
+    {
+        super::play_solo(system, table, ledger, league)
+    }
+
+    fn replay<A>(system: SnakeSystem, table: &mut [A]) -> Vec<MatchTrace>
+    where
+        A: Agent<<SnakeSystem as System>::Observation, Dir>,
+    {
+        super::replay_solo(system, table)
+    }
+
+    fn console(
+        p: &SnakeParams,
+    ) -> Option<Box<dyn Console<<SnakeSystem as System>::Observation, Dir>>> {
+        Some(Box::new(SnakeConsole { width: p.width, height: p.height }))
+    }
+}
@@ -0,0 +1,211 @@
+use serde::{Serialize, Deserialize};
+
+use nets::{
+    agent::Agent,
+    ledger::Ledger,
+    league::League,
+    match_trace::MatchTrace,
+    snake::Dir,
+};
+
+use super::arena::{run_arena_with_traces, ArenaObservation, SnakeArenaSystem};
+use super::console::Console;
+use super::guest::{Guest, Limits, Metered, Module};
+use super::snake::{u64_to_dir, SnakeConsole};
+
+/* ------------------------------
+   Guest adapter
+-------------------------------*/
+
+/// Most opponents a guest is told about; the nearest ones are kept.
+pub const MAX_OPPONENTS: usize = 3;
+
+pub struct SnakeArenaWasmAgent {
+    inner: Guest,
+}
+
+impl SnakeArenaWasmAgent {
+    pub fn load(agent_id: String, module: &Module, limits: &Limits) -> Result<Self, String> {
+        Ok(Self { inner: Guest::load(agent_id, module, limits)? })
+    }
+}
+
+impl Metered for SnakeArenaWasmAgent {
+    fn guest(&mut self) -> &mut Guest {
+        &mut self.inner
+    }
+}
+
+/// Pack an arena observation into the guest's `u64` input.
+///
+/// Coordinates are 6 bits each (boards are at most 63 wide):
+///
+/// | bits  | value                                        |
+/// |-------|----------------------------------------------|
+/// | 0-11  | own head x, y                                |
+/// | 12-23 | food x, y                                    |
+/// | 24-59 | up to 3 opponent heads x, y, nearest first   |
+/// | 60-61 | number of opponent heads present             |
+///
+/// Opponents are ordered by Manhattan distance from the own head, ties by
+/// seat; unused slots are zero.
+pub fn encode(obs: &ArenaObservation) -> u64 {
+    let coord = |(x, y): (i32, i32)| ((x as u64 & 0x3F) << 6) | (y as u64 & 0x3F);
+    let (hx, hy) = obs.head;
+
+    let mut opponents = obs.opponents.clone();
+    opponents.sort_by_key(|&(x, y)| (x - hx).abs() + (y - hy).abs());
+    opponents.truncate(MAX_OPPONENTS);
+
+    let mut packed = coord(obs.head) | (coord(obs.food) << 12);
+    for (i, &head) in opponents.iter().enumerate() {
+        packed |= coord(head) << (24 + 12 * i);
+    }
+    packed | ((opponents.len() as u64) << 60)
+}
+
+impl Agent<ArenaObservation, Dir> for SnakeArenaWasmAgent {
+    fn id(&self) -> String {
+        self.inner.id()
+    }
+
+    fn decide(&mut self, obs: ArenaObservation) -> Dir {
+        // A forfeited agent keeps heading up.
+        self.inner.decide(encode(&obs)).map(u64_to_dir).unwrap_or(Dir::Up)
+    }
+}
+
+/* ------------------------------
+   Terminal view
+-------------------------------*/
+
+/// Board as seen by one snake, row 0 on top: `H` its head, `o` the other
+/// heads, `*` food. Moves are typed as in `snake`.
+pub struct SnakeArenaConsole {
+    moves: SnakeConsole,
+}
+
+impl Console<ArenaObservation, Dir> for SnakeArenaConsole {
+    fn render(&self, obs: &ArenaObservation) -> String {
+        let mut board = String::new();
+        for y in 0..self.moves.height {
+            for x in 0..self.moves.width {
+                board.push(if (x, y) == obs.head {
+                    'H'
+                } else if obs.opponents.contains(&(x, y)) {
+                    'o'
+                } else if (x, y) == obs.food {
+                    '*'
+                } else {
+                    '.'
+                });
+            }
+            board.push('\n');
+        }
+        board.pop();
+        board
+    }
+
+    fn parse(&self, input: &str) -> Result<Dir, String> {
+        self.moves.parse(input)
+    }
+
+    fn show(&self, dir: &Dir) -> String {
+        self.moves.show(dir)
+    }
+
+    fn help(&self) -> &'static str {
+        self.moves.help()
+    }
+}
+
+/* ------------------------------
+   Registration
+-------------------------------*/
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnakeArenaParams {
+    pub width: i32,
+    pub height: i32,
+    pub steps: u64,
+    /// Snakes sharing one board.
+    pub seats: usize,
+}
+
+impl Default for SnakeArenaParams {
+    fn default() -> Self {
+        Self { width: 20, height: 20, steps: 300, seats: 4 }
+    }
+}
+
+pub struct SnakeArena;
+
+impl super::GameSystem for SnakeArena {
+    const NAME: &'static str = "snake-arena";
+
+    type Params = SnakeArenaParams;
+    type Sys = SnakeArenaSystem;
+    type Obs = ArenaObservation;
+    type Act = Dir;
+    type Adapter = SnakeArenaWasmAgent;
+
+    fn validate(p: &SnakeArenaParams) -> Result<(), String> {
+        if !(8..=63).contains(&p.width) || !(8..=63).contains(&p.height) {
+            return Err("width and height must be between 8 and 63".into());
+        }
+        if p.steps == 0 {
+            return Err("steps must be positive".into());
+        }
+        if !(2..=MAX_OPPONENTS + 1).contains(&p.seats) {
+            return Err(format!("seats must be between 2 and {}", MAX_OPPONENTS + 1));
+        }
+        Ok(())
+    }
+
+    fn seats(p: &SnakeArenaParams) -> usize {
+        p.seats
+    }
+
+    fn build(p: &SnakeArenaParams, seed: u64) -> SnakeArenaSystem {
+        SnakeArenaSystem::new(p.width, p.height, p.steps).with_seed(seed)
+    }
+
+    fn load_agent(
+        agent_id: String,
+        module: &Module,
+        limits: &Limits,
+        _encoding: u32,
+    ) -> Result<SnakeArenaWasmAgent, String> {
+        SnakeArenaWasmAgent::load(agent_id, module, limits)
+    }
+
+    /// Every snake of `table` on one board. Each seat's trace holds its
+    /// own observations and moves, which depend on every other seat.
+    fn play<A>(
+        system: SnakeArenaSystem,
+        table: &mut [A],
+        _ledger: &Ledger,
+        _league: &League,
+    ) -> Vec<(i64, usize, MatchTrace)>
+    where
+        A: Agent<ArenaObservation, Dir>,
+    {
+        let (scores, traces) = run_arena_with_traces(system, table);
+        scores.into_iter().zip(traces).map(|(score, trace)| (score, 1, trace)).collect()
+    }
+
+    /// Shown in `nets replay`; the board is shared, so it cannot be
+    /// played by one person at the terminal.
+    fn console(p: &SnakeArenaParams) -> Option<Box<dyn Console<ArenaObservation, Dir>>> {
+        let moves = SnakeConsole { width: p.width, height: p.height };
+        Some(Box::new(SnakeArenaConsole { moves }))
+    }
+
+    fn replay<A>(system: SnakeArenaSystem, table: &mut [A]) -> Vec<MatchTrace>
+    where
+        A: Agent<ArenaObservation, Dir>,
+    {
+        run_arena_with_traces(system, table).1
+    }
+}
@@ -69,4 +69,8 @@ impl MockWalletAdapter {
     pub fn all_wallets(&self) -> &HashMap<String, u64> {
         &self.wallet_balances
     }
+
+    pub fn wallet_of(&self, agent_id: &str) -> Option<&str> {
+        self.agent_to_wallet.get(agent_id).map(|w| w.as_str())
+    }
 }
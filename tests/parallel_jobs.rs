@@ -0,0 +1,114 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/* ------------------------------
+   nets run --jobs
+-------------------------------*/
+
+// `--jobs` only changes how matches are spread over threads, so the state
+// a committed run leaves behind, commitments included, must not depend on
+// it. Each run gets its own workspace with the same agents.
+
+/// A guest whose `decide` answers `body` with its input on the stack.
+fn agent(body: &str) -> Vec<u8> {
+    let module = format!(
+        r#"(module
+            (memory (export "memory") 1)
+            (func (export "decide") (param i64) (result i64)
+                local.get 0
+                {}))"#,
+        body
+    );
+    wat::parse_str(module).unwrap()
+}
+
+/// Agents that play differently enough for scores to differ.
+fn agents() -> Vec<(&'static str, Vec<u8>)> {
+    vec![
+        ("a", agent("drop i64.const 0")),
+        ("b", agent("drop i64.const 1")),
+        ("c", agent("drop i64.const 2")),
+        ("d", agent("drop i64.const 3")),
+        ("e", agent("i64.const 3 i64.rem_u")),
+        ("f", agent("i64.const 7 i64.shr_u i64.const 4 i64.rem_u")),
+    ]
+}
+
+fn workspace(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("nets-jobs-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("agents")).unwrap();
+    for (id, wasm) in agents() {
+        fs::write(dir.join("agents").join(format!("{}.wasm", id)), wasm).unwrap();
+    }
+    dir
+}
+
+fn run(dir: &Path, system: &str, seed: u64, jobs: usize) {
+    let out = Command::new(env!("CARGO_BIN_EXE_nets"))
+        .arg("--workspace")
+        .arg(dir)
+        .args(["run", "--system", system, "--matches", "3", "--commit"])
+        .args(["--seed", &seed.to_string(), "--jobs", &jobs.to_string()])
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "nets run --jobs {} failed:\n{}",
+        jobs,
+        String::from_utf8_lossy(&out.stderr)
+    );
+}
+
+/// state.json after two committed runs with `jobs` worker threads.
+fn state_after_runs(system: &str, jobs: usize) -> Vec<u8> {
+    let dir = workspace(&format!("{}-{}", system, jobs));
+    run(&dir, system, 7, jobs);
+    run(&dir, system, 8, jobs);
+    let state = fs::read(dir.join("state.json")).unwrap();
+    assert_traces_written(&dir, &state);
+    let _ = fs::remove_dir_all(&dir);
+    state
+}
+
+/// Committed matches keep their steps in trace files, not in state.json.
+fn assert_traces_written(dir: &Path, state: &[u8]) {
+    let json: serde_json::Value = serde_json::from_slice(state).unwrap();
+    for record in json["commitment_records"].as_object().unwrap().values() {
+        for m in record["matches"].as_array().unwrap() {
+            assert!(m.get("steps").is_none(), "step hashes stored in state.json");
+            let name = m["trace"].as_str().expect("committed match without a trace file");
+            let trace: serde_json::Value =
+                serde_json::from_slice(&fs::read(dir.join("traces").join(name)).unwrap()).unwrap();
+            assert_eq!(trace["merkle_root"], m["merkle_root"]);
+        }
+    }
+}
+
+fn assert_jobs_do_not_matter(system: &str) {
+    let serial = state_after_runs(system, 1);
+    let parallel = state_after_runs(system, 4);
+
+    let json: serde_json::Value = serde_json::from_slice(&serial).unwrap();
+    let records = json["commitment_records"].as_object().expect("no commitments recorded");
+    assert_eq!(records.len(), agents().len());
+
+    assert!(
+        serial == parallel,
+        "{} state differs between --jobs 1 and --jobs 4:\n{}\n---\n{}",
+        system,
+        String::from_utf8_lossy(&serial),
+        String::from_utf8_lossy(&parallel)
+    );
+}
+
+#[test]
+fn single_seat_runs_are_identical_for_any_jobs() {
+    assert_jobs_do_not_matter("rps");
+}
+
+#[test]
+fn shared_board_runs_are_identical_for_any_jobs() {
+    assert_jobs_do_not_matter("snake-arena");
+}